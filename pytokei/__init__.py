@@ -1,18 +1,26 @@
 # fmt: off
 from pytokei._pytokei import (CodeStats, Config,  # type: ignore[attr-defined]
-                              Language, Languages, LanguageType, Report, Sort,
-                              __version__, sort_types)
+                              FileDiff, History, Language, LanguageDiff,
+                              Languages, LanguageType, Report, ReportsIterator,
+                              Sort, __version__, history, iter_reports,
+                              sort_types)
 
 # fmt: on
 
 __all__ = [
     "CodeStats",
     "Config",
+    "FileDiff",
+    "History",
     "Language",
+    "LanguageDiff",
     "Languages",
     "LanguageType",
     "Report",
+    "ReportsIterator",
     "Sort",
+    "history",
+    "iter_reports",
     "sort_types",
     "__version__",
 ]
@@ -1,4 +1,4 @@
-from typing import Optional
+from typing import Any, Callable, Iterator, Optional
 
 class Config:
     """A configuration struct for how Languages.get_statistics searches and counts languages.
@@ -18,11 +18,13 @@ class Config:
 
     def __init__(self) -> None: ...
     @property
-    def columns(self) -> int:
-        """Width of columns to be printed to the terminal. This option is ignored in the library.
-        Default: *Auto detected width of the terminal*.
+    def columns(self) -> Optional[int]:
+        """Width of columns to be printed to the terminal. Only used by `Languages.format_table`.
+        Default: *None*.
         [tokei reference](https://docs.rs/tokei/latest/tokei/struct.Config.html#structfield.columns).
         """
+    @columns.setter
+    def columns(self, columns: Optional[int]) -> None: ...
     @property
     def hidden(self) -> bool:
         """Count hidden files and directories.
@@ -58,6 +60,182 @@ class Config:
         Default: *false*.
         [tokei reference](https://docs.rs/tokei/latest/tokei/struct.Config.html#structfield.treat_doc_strings_as_comments).
         """
+    @property
+    def sort(self) -> Optional[Sort]:
+        """Sort languages. Only used by `Languages.format_table`.
+        Default: *None*.
+        [tokei reference](https://docs.rs/tokei/latest/tokei/struct.Config.html#structfield.sort).
+        """
+    @sort.setter
+    def sort(self, sort: Optional[Sort]) -> None: ...
+    @property
+    def cache_dir(self) -> Optional[str]:
+        """Directory where the statistics of each file are stored, keyed by the hash
+        of its content and the configuration used. When set, files whose content was
+        already counted aren't parsed again, even across different runs.
+        The directory is created if it doesn't exist. Not available in tokei.
+        Default: *None*.
+        """
+    @cache_dir.setter
+    def cache_dir(self, cache_dir: Optional[str]) -> None: ...
+    @property
+    def include(self) -> list[str]:
+        """Patterns of the files to count, the rest are skipped. Used by the directory walker
+        (`Languages.get_statistics`, `Languages.update`, `Languages.watch` and `iter_reports`)
+        and with the files of git revisions and archives, like in `history` or
+        `Languages.get_statistics_archive`.
+
+        The patterns follow the `.gitignore` syntax, and are matched against the path of
+        each file relative to the path being walked, the repository path or the root of
+        the archive: `*.rs` matches a file at any depth, `src/**/*.rs` or `/src` only from
+        the root, a pattern matching a directory includes every file inside, and
+        `!pattern` removes the files matched by a previous pattern.
+        The paths given explicitly are always counted. Not available in tokei.
+        Default: *[]*, every file is counted.
+
+        Raises
+        ------
+            ValueError
+                When set, if a pattern is not valid.
+        """
+    @include.setter
+    def include(self, include: Optional[list[str]]) -> None: ...
+    @property
+    def exclude(self) -> list[str]:
+        """Patterns of the files and directories to skip, with the same syntax as `include`.
+        A file is skipped if it's excluded, even if it's included too. Not available in tokei.
+        Default: *[]*.
+
+        Raises
+        ------
+            ValueError
+                When set, if a pattern is not valid.
+        """
+    @exclude.setter
+    def exclude(self, exclude: Optional[list[str]]) -> None: ...
+    @property
+    def include_regex(self) -> Optional[str]:
+        """Regular expression searched in the path of each file relative to the path being
+        walked (with `/` as separator), only the files where it's found are counted.
+        Use `^` and `$` to match the whole path. Not available in tokei.
+        Default: *None*.
+
+        Raises
+        ------
+            ValueError
+                When set, if the regular expression is not valid.
+        """
+    @include_regex.setter
+    def include_regex(self, include_regex: Optional[str]) -> None: ...
+    @property
+    def exclude_regex(self) -> Optional[str]:
+        """Regular expression searched in the path of each file like `include_regex`,
+        the files where it's found are skipped. Not available in tokei.
+        Default: *None*.
+
+        Raises
+        ------
+            ValueError
+                When set, if the regular expression is not valid.
+        """
+    @exclude_regex.setter
+    def exclude_regex(self, exclude_regex: Optional[str]) -> None: ...
+    @property
+    def ignore_files(self) -> list[str]:
+        """Paths of ignore files applied to every path walked, like a central ignore file
+        shared by several repositories. Their patterns follow the `.gitignore` syntax and
+        are relative to each path walked, as if the file was at its root.
+        Not used if `no_ignore` is set. Not available in tokei.
+        Default: *[]*.
+
+        Raises
+        ------
+            ValueError
+                When set, if a file contains an invalid pattern.
+            OSError
+                When set, if a file can't be read.
+        """
+    @ignore_files.setter
+    def ignore_files(self, ignore_files: Optional[list[str]]) -> None: ...
+    @property
+    def custom_ignore_filenames(self) -> list[str]:
+        """Names of the ignore files looked for in every directory, besides `.gitignore`,
+        `.ignore` and `.tokeignore`, like `.locignore`. They take precedence over the
+        rest of ignore files, the later names over the earlier ones.
+        Not used if `no_ignore` or `no_ignore_dot` are set. Not available in tokei.
+        Default: *[]*.
+
+        Raises
+        ------
+            ValueError
+                When set, if a name isn't a file name, like `dir/.locignore`.
+        """
+    @custom_ignore_filenames.setter
+    def custom_ignore_filenames(self, custom_ignore_filenames: Optional[list[str]]) -> None: ...
+    @property
+    def follow_links(self) -> bool:
+        """Whether to follow the symbolic links when walking the directories. Loops
+        of links are skipped. Not available in tokei.
+        Default: *False*.
+        """
+    @follow_links.setter
+    def follow_links(self, follow_links: bool) -> None: ...
+    @property
+    def max_depth(self) -> Optional[int]:
+        """Maximum depth of the directories walked, the paths given are at depth 0 so
+        with 1 only the files directly inside them are counted. Not available in tokei.
+        Default: *None*, no limit.
+        """
+    @max_depth.setter
+    def max_depth(self, max_depth: Optional[int]) -> None: ...
+    @property
+    def same_file_system(self) -> bool:
+        """Whether to stay in the file system of each path walked, without entering
+        other mount points like `/proc`. Not available in tokei.
+        Default: *False*.
+        """
+    @same_file_system.setter
+    def same_file_system(self, same_file_system: bool) -> None: ...
+    @property
+    def max_file_size(self) -> Optional[int]:
+        """Size in bytes above which the files are skipped, also those of git revisions and
+        archives, they are listed in `Languages.skipped`. Not available in tokei.
+        Default: *None*, no limit.
+        """
+    @max_file_size.setter
+    def max_file_size(self, max_file_size: Optional[int]) -> None: ...
+    @property
+    def skip_binary(self) -> bool:
+        """Whether to skip the files containing null bytes once decoded, they are listed
+        in `Languages.skipped`. Not available in tokei.
+        Default: *False*.
+        """
+    @skip_binary.setter
+    def skip_binary(self, skip_binary: bool) -> None: ...
+    @property
+    def skip_minified(self) -> bool:
+        """Whether to skip the minified files, those whose lines have more than 200 bytes
+        on average, like bundles of javascript. They are listed in `Languages.skipped`.
+        Not available in tokei.
+        Default: *False*.
+        """
+    @skip_minified.setter
+    def skip_minified(self, skip_minified: bool) -> None: ...
+    @property
+    def test_patterns(self) -> list[str]:
+        """Patterns of the test files, like `e2e/` or `*_check.py`, added to the default ones:
+        directories like `tests/` or `spec/` and the names of each language, like
+        `*_test.go` or `test_*.py`. They follow the `.gitignore` syntax and set
+        `Report.is_test`. Not available in tokei.
+        Default: *[]*.
+
+        Raises
+        ------
+            ValueError
+                When set, if any of the patterns is invalid.
+        """
+    @test_patterns.setter
+    def test_patterns(self, test_patterns: Optional[list[str]]) -> None: ...
     @staticmethod
     def from_config_files() -> Config:
         """Creates a Config from three configuration files if they are available.
@@ -145,6 +323,34 @@ class Language:
     def files(self) -> int:
         """Counts the number of reports."""
     def __repr__(self) -> str: ...
+    def __add__(self, other: Language | Report) -> Language:
+        """Adds the counts, reports and children of another language, or a single report.
+
+        Mirrors the `AddAssign` of tokei, also available as `+=`. The reports are
+        not deduplicated.
+
+        Examples
+        --------
+        ```python
+        >>> python = langs[LanguageType("Python")]
+        >>> (python + python).code == 2 * python.code
+        True
+        >>> sum(python.reports) == python
+        True
+        ```
+        """
+    def __iadd__(self, other: Language | Report) -> Language: ...
+    def __radd__(self, other: int) -> Language:
+        """Allows using `sum` on a list of languages."""
+    def __sub__(self, other: Language) -> Language:
+        """Subtracts the counts of other, and removes the reports of the files found in it.
+
+        Raises
+        ------
+            ValueError
+                If any of the counts would be negative.
+        """
+    def __eq__(self, other: object) -> bool: ...
 
 class LanguageType:
     """Represents a individual programming language.
@@ -162,7 +368,7 @@ class LanguageType:
     Warning:
         The following methods aren't currently implemented:
         `from_path`, `from_file_extension`, `from_mime`,
-        `from_shebang`, `parse`, `parse_from_str`, `parse_from_slice`.
+        `from_shebang`, `parse`, `parse_from_slice`.
 
     Examples
     --------
@@ -283,6 +489,58 @@ class LanguageType:
         """Returns the parts of syntax that determines whether `tokei`
         can skip large parts of analysis.
         """
+    def parse_from_str(self, text: str, config: Config) -> CodeStats:
+        """Counts the lines of a text of this language, as `tokei` counts a file.
+
+        Parameters
+        ----------
+        text : str
+            Contents to count, like the ones of a file.
+        config : Config
+            Config object, see [Config](config.md).
+
+        Returns
+        -------
+        stats : CodeStats
+            The lines of the text, with the embedded languages in `CodeStats.blobs`.
+
+        Examples
+        --------
+        ```python
+        >>> LanguageType("Rust").parse_from_str("// comment\nfn main() {}\n", Config())
+        CodeStats(blanks: 0, code: 1, comments: 1, lines: 2)
+        ```
+        """
+    def classify_lines(
+        self, text: str, config: Config
+    ) -> list[tuple[Optional[str], Optional[LanguageType]]]:
+        """Classifies each line of a text of this language, the same way `tokei` counts them.
+
+        Parameters
+        ----------
+        text : str
+            Contents to classify, like the ones of a file.
+        config : Config
+            Config object, see [Config](config.md).
+
+        Returns
+        -------
+        lines : list[tuple[Optional[str], Optional[LanguageType]]]
+            For each line, its kind (`code`, `comment` or `blank`) and the language
+            of the embedded block it belongs to, like the code blocks of markdown or
+            the scripts of html, or None if it is of this language.
+            The kind is None for the lines that tokei doesn't count, like the
+            trailing blank lines of a code block. The few lines tokei counts twice,
+            like a closing fence followed by `\r\n`, take the first kind counted.
+
+        Examples
+        --------
+        ```python
+        >>> text = "# Title\n\n```rust\nfn main() {}\n```\n"
+        >>> LanguageType("Markdown").classify_lines(text, Config())
+        [('comment', None), ('blank', None), ('comment', None), ('code', LanguageType(Rust)), ('comment', None)]
+        ```
+        """
 
 class Languages:
     """A class representing a list of languages counted in the provided directory.
@@ -312,6 +570,8 @@ class Languages:
         absolute or glob paths.
         A second list of paths (as str) to ignore, these strings use the `.gitignore` syntax,
         such as `target` or `**/*.bk`.
+        To select the files to count, prefer `Config.include` and `Config.exclude`, whose
+        patterns are relative to each path walked.
 
         Parameters
         ----------
@@ -321,6 +581,260 @@ class Languages:
                 List of files to ignore. If you don't want anything ignored, just pass `["ignored"]`.
             config : Config
                 Config instance. If you dont have any preferences, just pass `Config`.
+
+        Raises
+        ------
+            ValueError
+                If no path is given or the ignored patterns are not valid.
+            OSError
+                If `Config.cache_dir` is set but can't be created.
+        """
+    def get_statistics_archive(self, path: str, config: Config) -> None:
+        """Populates the Languages struct with the statistics of the files of an archive.
+
+        The archive can be a tar file, compressed with gzip or not, or a zip file. The
+        format is detected from the content, and the entries are read one by one without
+        extracting them to the disk. The language is detected from the name of each
+        entry (or its shebang), hidden files are skipped unless `Config.hidden` is set,
+        the ignore files inside the archive aren't used.
+
+        The names of the reports are the paths of the files inside the archive.
+
+        Parameters
+        ----------
+            path : str
+                Path to the archive.
+            config : Config
+                Config instance.
+
+        Examples
+        --------
+        ```python
+        >>> langs = Languages()
+        >>> langs.get_statistics_archive("vendor-drop.tar.gz", Config())
+        >>> langs.files()
+        {'C': 42, 'C Header': 17, 'Makefile': 1}
+        >>> langs[LanguageType("Makefile")].reports
+        [Report("vendor-1.2/Makefile")]
+        ```
+
+        Raises
+        ------
+            ValueError
+                If the file isn't a valid tar, tar.gz or zip archive, or it's corrupt.
+            IOError
+                If the archive can't be read.
+        """
+    def get_statistics_at_revision(self, repo_path: str, rev: str, config: Config) -> None:
+        """Populates the Languages struct with the statistics of the files of a git revision.
+
+        The files are read directly from the git object database of the local
+        repository, without checking out the revision. The `.gitignore` (and `.ignore`,
+        `.tokeignore`) files of the revision are honoured as `get_statistics` does.
+        Requires `git` to be installed.
+
+        The names of the reports are the paths of the files joined to `repo_path`,
+        the same that would be obtained calling `get_statistics([repo_path], ...)`.
+
+        Parameters
+        ----------
+            repo_path : str
+                Path to the local git repository, or to a directory inside it to count
+                only the files below it.
+            rev : str
+                Revision to count, like a tag, a branch or a commit hash.
+            config : Config
+                Config instance.
+
+        Examples
+        --------
+        ```python
+        >>> langs = Languages()
+        >>> langs.get_statistics_at_revision(".", "v0.2.0", Config())
+        >>> langs.total_plain()
+        {'files': 13, 'lines': 593, 'code': 492, 'comments': 50, 'blanks': 51}
+        ```
+
+        Raises
+        ------
+            ValueError
+                If the path is not a git repository or the revision doesn't exist.
+            IOError
+                If git can't be run or the objects can't be read.
+        """
+    def get_statistics_by_author(
+        self, repo_path: str, rev: str, config: Config
+    ) -> dict[str, dict[LanguageType, CodeStats]]:
+        """Counts the lines of a git revision written by each author.
+
+        Every line counted is attributed with `git blame` to the author of the commit
+        that last changed it, and classified as code, comment or blank with the same
+        rules tokei uses to count the whole file. The lines of embedded languages are
+        counted in the language of the file, and the few lines tokei skips aren't
+        attributed, so the totals of the authors are the ones of the revision.
+        The `.mailmap` of the repository is used for the names of the authors.
+
+        The Languages struct is populated with the statistics of the revision, as
+        `get_statistics_at_revision` does. Requires `git` to be installed.
+
+        Parameters
+        ----------
+            repo_path : str
+                Path to the local git repository, or to a directory inside it to count
+                only the files below it.
+            rev : str
+                Revision to count, like a tag, a branch or a commit hash.
+            config : Config
+                Config instance.
+
+        Returns
+        -------
+            dict[str, dict[LanguageType, CodeStats]]
+                The lines of each author per language.
+
+        Examples
+        --------
+        ```python
+        >>> langs = Languages()
+        >>> authors = langs.get_statistics_by_author(".", "HEAD", Config())
+        >>> authors["John Doe"]
+        {LanguageType(Rust): CodeStats(blanks: 52, code: 430, comments: 38, lines: 520), LanguageType(Python): CodeStats(blanks: 41, code: 150, comments: 12, lines: 203)}
+        ```
+
+        Raises
+        ------
+            ValueError
+                If the path is not a git repository or the revision doesn't exist.
+            IOError
+                If git can't be run or the objects can't be read.
+        """
+    def get_statistics_for_diff(
+        self, repo_path: str, base: str, head: str, config: Config
+    ) -> dict[LanguageType, dict[str, CodeStats]]:
+        """Counts the lines added and removed per language between two git revisions.
+
+        Every line of a changed file is classified as code, comment or blank
+        with the same rules tokei uses to count the whole file, so the lines removed
+        are classified in the `base` version and the lines added in the `head` one.
+        The changed lines tokei doesn't count aren't added to any kind.
+        Files whose language changes between the revisions are counted as removed
+        and added, and the files skipped by the limits of the `Config` in a revision
+        as if they weren't in it.
+
+        The Languages struct is populated only with the files changed, as they are
+        in `head`. The ignore files are honoured in each revision as
+        `get_statistics_at_revision` does. Requires `git` to be installed.
+
+        Parameters
+        ----------
+            repo_path : str
+                Path to the local git repository, or to a directory inside it to count
+                only the files below it.
+            base : str
+                Revision to compare with, like the target branch of a pull request.
+            head : str
+                Revision containing the changes.
+            config : Config
+                Config instance.
+
+        Returns
+        -------
+            dict[LanguageType, dict[str, CodeStats]]
+                The lines `added` and `removed` of each language with changes.
+
+        Examples
+        --------
+        ```python
+        >>> langs = Languages()
+        >>> diff = langs.get_statistics_for_diff(".", "main", "HEAD", Config())
+        >>> diff[LanguageType("Rust")]
+        {'added': CodeStats(blanks: 6, code: 71, comments: 4, lines: 81), 'removed': CodeStats(blanks: 0, code: 3, comments: 0, lines: 3)}
+        >>> langs.files()
+        {'Rust': 2}
+        ```
+
+        Raises
+        ------
+            ValueError
+                If the path is not a git repository or a revision doesn't exist.
+            IOError
+                If git can't be run or the objects can't be read.
+        """
+    def update(
+        self, paths: list[str], ignored: list[str], config: Config
+    ) -> dict[str, list[str]]:
+        """Updates the statistics obtained with `get_statistics` parsing only what changed.
+
+        Only the files whose modification time or size changed since the previous call
+        are parsed again, the reports of the files no longer found are removed,
+        and the totals are recomputed. It should be called with the same arguments
+        passed to `get_statistics`.
+
+        Parameters
+        ----------
+            paths : list[str]
+                List of files to traverse. It may be a single directory.
+            ignored : list[str]
+                List of files to ignore.
+            config : Config
+                Config instance.
+
+        Returns
+        -------
+            dict[str, list[str]]
+                The paths of the files `added`, `modified` and `removed`.
+
+        Examples
+        --------
+        ```python
+        >>> langs.get_statistics(["."], ["target"], conf)
+        >>> # ... some time later
+        >>> langs.update(["."], ["target"], conf)
+        {'added': [], 'modified': ['./src/lib.rs'], 'removed': []}
+        ```
+        """
+    def watch(
+        self,
+        paths: list[str],
+        config: Config,
+        callback: Callable[[dict[str, list[Report]], dict[str, int]], Optional[bool]],
+        ignored: Optional[list[str]] = None,
+        timeout: Optional[float] = None,
+    ) -> None:
+        """Keeps the statistics up to date while the files are created, modified or deleted.
+
+        Blocks watching the directories for changes (using inotify, only available on linux).
+        Every time some file changes, the statistics are updated as in `update` and
+        `callback` is called with the reports `added`, `modified` and `removed`,
+        and the new totals (as in `total_plain`).
+
+        Parameters
+        ----------
+            paths : list[str]
+                List of files to traverse. It may be a single directory.
+            config : Config
+                Config instance.
+            callback : Callable
+                Called after each update. Returning `False` stops watching.
+            ignored : Optional[list[str]]
+                List of files to ignore. Defaults to None.
+            timeout : Optional[float]
+                Seconds to watch before returning. By default watches until
+                the callback returns `False` or the program is interrupted.
+
+        Examples
+        --------
+        ```python
+        >>> def show(changes, totals):
+        ...     print(changes["modified"], totals["code"])
+        >>> langs.watch(["."], conf, show)
+        [Report("./src/lib.rs")] 1293
+        ```
+
+        Raises
+        ------
+            NotImplementedError
+                If the platform is not linux.
         """
     def total(self) -> Language:
         """Summary of the Languages struct."""
@@ -336,6 +850,180 @@ class Languages:
         """Exposes the inner struct from rust to the classes defined in python."""
     def files(self) -> dict[str, int]:
         """Total number of files in the value, corresponding to the language name key."""
+    def filter(
+        self,
+        generated: Optional[bool] = None,
+        vendored: Optional[bool] = None,
+        test: Optional[bool] = None,
+    ) -> Languages:
+        """Copy with only the reports whose `Report.is_generated`, `Report.is_vendored`
+        and `Report.is_test` have the values given, to obtain the totals with or without them.
+
+        Parameters
+        ----------
+            generated : Optional[bool]
+                Value of `Report.is_generated` of the reports kept, all if None.
+            vendored : Optional[bool]
+                Value of `Report.is_vendored` of the reports kept, all if None.
+            test : Optional[bool]
+                Value of `Report.is_test` of the reports kept, all if None.
+
+        Returns
+        -------
+            languages : Languages
+                New instance with the reports filtered.
+
+        Examples
+        --------
+        ```python
+        >>> langs = pytokei.Languages()
+        >>> langs.get_statistics(["."], [], pytokei.Config())
+        >>> own = langs.filter(generated=False, vendored=False)
+        >>> own.total_plain()
+        {'blanks': 562, 'code': 3718, 'comments': 339, 'files': 31, 'lines': 4619}
+        ```
+        """
+    def split_by(self, category: str) -> dict[str, Language]:
+        """Totals of the reports in and out of a category, like `Languages.total`.
+
+        Parameters
+        ----------
+            category : str
+                One of `test`, `generated` or `vendored`. The totals are keyed by
+                `test` and `production`, `generated` and `handwritten`, or `vendored`
+                and `first_party` respectively.
+
+        Returns
+        -------
+            totals : dict[str, Language]
+                Totals of each part, with the reports of each language as children.
+
+        Raises
+        ------
+            ValueError
+                If the category is not one of the above.
+
+        Examples
+        --------
+        ```python
+        >>> langs = pytokei.Languages()
+        >>> langs.get_statistics(["."], [], pytokei.Config())
+        >>> split = langs.split_by("test")
+        >>> split["test"].code, split["production"].code
+        (1174, 2544)
+        ```
+        """
+    def tree(
+        self, depth: int = 1, root: Optional[str] = None
+    ) -> dict[str, dict[LanguageType, CodeStats]]:
+        """Statistics of each directory, per language, including the files of its
+        subdirectories.
+
+        Parameters
+        ----------
+            depth : int
+                Levels of directories below the root, defaults to 1.
+            root : Optional[str]
+                Directory the paths are relative to, defaults to the deepest directory
+                containing all the files. The files outside it are not counted.
+
+        Returns
+        -------
+            tree : dict[str, dict[LanguageType, CodeStats]]
+                Statistics keyed by the path of each directory relative to the root,
+                with `/` as separator and `.` for the root itself.
+
+        Examples
+        --------
+        ```python
+        >>> langs = pytokei.Languages()
+        >>> langs.get_statistics(["."], ["target"], pytokei.Config())
+        >>> tree = langs.tree(depth=1)
+        >>> {path: sum(s.code for s in stats.values()) for path, stats in tree.items()}
+        {'.': 6344, 'bench': 27, 'docs': 0, 'pytokei': 152, 'requirements': 7, 'src': 5000, 'tests': 1013}
+        ```
+        """
+    def group_by_codeowners(
+        self, path: str, root: Optional[str] = None
+    ) -> dict[str, dict[LanguageType, CodeStats]]:
+        """Statistics of each owner according to a CODEOWNERS file, per language.
+
+        Follows the rules of github and gitlab: the last pattern matching a file gives
+        its owners, and the sections of gitlab, like `[Backend] @backend-team`, are
+        matched independently. The files with several owners are counted for each of
+        them, and the ones without owners in the `unowned` group.
+
+        Parameters
+        ----------
+            path : str
+                Path to the CODEOWNERS file.
+            root : Optional[str]
+                Root of the repository, the patterns are relative to it. Defaults to the
+                directory of the file, or its parent if it's in `.github`, `.gitlab`
+                or `docs`.
+
+        Returns
+        -------
+            owners : dict[str, dict[LanguageType, CodeStats]]
+                Statistics keyed by each owner, like `@org/team`.
+
+        Raises
+        ------
+            ValueError
+                If the file contains invalid patterns.
+            OSError
+                If the file can't be read.
+
+        Examples
+        --------
+        ```python
+        >>> langs = pytokei.Languages()
+        >>> langs.get_statistics(["."], [], pytokei.Config())
+        >>> owners = langs.group_by_codeowners(".github/CODEOWNERS")
+        >>> {owner: sum(s.code for s in stats.values()) for owner, stats in owners.items()}
+        {'@org/backend': 5048, '@org/frontend': 1121, 'unowned': 175}
+        ```
+        """
+    def embedded_totals(self) -> dict[LanguageType, dict[LanguageType, CodeStats]]:
+        """Statistics of the code embedded in the files of each language, like the code
+        blocks of markdown or the cells of jupyter notebooks, see `Report.embedded`.
+
+        Returns
+        -------
+            embedded : dict[LanguageType, dict[LanguageType, CodeStats]]
+                For each language with embedded code, the totals of every language
+                embedded in its files.
+
+        Examples
+        --------
+        ```python
+        >>> langs = pytokei.Languages()
+        >>> langs.get_statistics(["."], [], pytokei.Config())
+        >>> langs.embedded_totals()[LanguageType("Markdown")]
+        {LanguageType(Python): CodeStats(blanks: 0, code: 203, comments: 0, lines: 203), LanguageType(BASH): CodeStats(blanks: 0, code: 17, comments: 0, lines: 17)}
+        ```
+        """
+    def skipped(self) -> list[tuple[str, str]]:
+        """Files found but not counted, sorted by path, due to the limits of the
+        `Config`. The reason is one of `max_file_size`, `binary` or `minified`.
+
+        Returns
+        -------
+            skipped : list[tuple[str, str]]
+                Path and reason of each file skipped.
+
+        Examples
+        --------
+        ```python
+        >>> conf = pytokei.Config()
+        >>> conf.max_file_size = 100_000
+        >>> conf.skip_minified = True
+        >>> langs = pytokei.Languages()
+        >>> langs.get_statistics(["."], [], conf)
+        >>> langs.skipped()
+        [('./static/app.min.js', 'minified'), ('./tests/data/huge.sql', 'max_file_size')]
+        ```
+        """
     def get_languages_plain(self) -> dict[str, list[dict[str, dict[str, int]]]]:
         """The same method as `get_languages` but in python builtin objects."""
     def total_plain(self) -> dict[str, int]:
@@ -344,6 +1032,181 @@ class Languages:
         """Returns the information as the default command from tokei.
         Computes the statistics per language.
         """
+    def to_markdown(
+        self, files: bool = False, totals: bool = True, sort: Optional[Sort] = None
+    ) -> str:
+        """Renders the compact report as a markdown table.
+
+        Contains the same columns as `report_compact_plain`: language, files,
+        lines, code, comments and blanks.
+
+        Parameters
+        ----------
+            files : bool
+                Add a row for each file after the language it belongs to. Defaults to False.
+            totals : bool
+                Add a final row with the totals. Defaults to True.
+            sort : Optional[Sort]
+                Sort the languages and the files by this column, in descending order.
+                Defaults to `Config.sort` if it was set, otherwise they are sorted by name.
+
+        Examples
+        --------
+        ```python
+        >>> print(langs.to_markdown())
+        | Language | Files | Lines | Code | Comments | Blanks |
+        |:---|---:|---:|---:|---:|---:|
+        | Python | 2 | 34 | 22 | 4 | 8 |
+        | **Total** | **2** | **34** | **22** | **4** | **8** |
+        ```
+        """
+    def to_html(
+        self, files: bool = False, totals: bool = True, sort: Optional[Sort] = None
+    ) -> str:
+        """Renders the compact report as an html `<table>`.
+
+        The rows have the classes `language`, `file` and `total` to allow styling them,
+        the totals are placed in the `<tfoot>` of the table.
+
+        Parameters
+        ----------
+            files : bool
+                Add a row for each file after the language it belongs to. Defaults to False.
+            totals : bool
+                Add a final row with the totals. Defaults to True.
+            sort : Optional[Sort]
+                Sort the languages and the files by this column, in descending order.
+                Defaults to `Config.sort` if it was set, otherwise they are sorted by name.
+        """
+    def badge(
+        self,
+        metric: str = "code",
+        language: Optional[LanguageType] = None,
+        style: str = "flat",
+    ) -> str:
+        """Renders a shields-style badge as a self-contained SVG string.
+
+        The values are the same obtained in `report_compact_plain`, large
+        numbers are abbreviated (`12.3k`).
+
+        Parameters
+        ----------
+            metric : str
+                One of `code`, `lines`, `comments`, `blanks` or `files`. Defaults to `code`.
+            language : Optional[LanguageType]
+                Restrict the count to a single language. By default uses the totals.
+            style : str
+                One of `flat` or `flat-square`. Defaults to `flat`.
+
+        Examples
+        --------
+        ```python
+        >>> svg = langs.badge("lines", LanguageType("Rust"))
+        >>> pathlib.Path("loc.svg").write_text(svg)
+        ```
+
+        Raises
+        ------
+            ValueError
+                If the metric or style are not valid, or the language wasn't found.
+        """
+    def format_table(
+        self,
+        columns: Optional[int] = None,
+        files: bool = False,
+        compact: bool = False,
+        sort: Optional[Sort] = None,
+    ) -> str:
+        """Reproduces the table printed by the `tokei` command line.
+
+        Includes the rows of the child languages (like `|- Markdown`) and the `Total` line.
+        When `columns` or `sort` are not given, the values of the `Config` used in
+        `get_statistics` are used instead.
+
+        Parameters
+        ----------
+            columns : Optional[int]
+                Width of the table. Defaults to `Config.columns`, or 79 as `tokei` does.
+                Smaller values are not allowed.
+            files : bool
+                Show the statistics of every file, like `tokei --files`. Defaults to False.
+            compact : bool
+                Don't show the statistics of the child languages, like `tokei --compact`.
+                Defaults to False.
+            sort : Optional[Sort]
+                Sort the languages and files. Defaults to `Config.sort`.
+
+        Examples
+        --------
+        ```python
+        >>> print(langs.format_table())
+        ===============================================================================
+         Language            Files        Lines         Code     Comments       Blanks
+        ===============================================================================
+         Dockerfile              1           16            7            3            6
+        ...
+        ```
+        """
+    def merge(self, other: Languages) -> None:
+        """Adds the reports of `other` to these statistics and recomputes the totals.
+
+        Allows combining the results of shards scanned separately. The files are
+        deduplicated by path, when a file is in both objects the report already
+        present is kept.
+
+        `langs + other` returns a new object with the merged statistics,
+        and `langs += other` is the same as `langs.merge(other)`.
+
+        Parameters
+        ----------
+            other : Languages
+                Statistics to combine with.
+
+        Examples
+        --------
+        ```python
+        >>> shard_a.files(), shard_b.files()
+        ({'Python': 2}, {'Python': 1, 'Rust': 3})
+        >>> (shard_a + shard_b).files()
+        {'Python': 3, 'Rust': 3}
+        ```
+        """
+    def __add__(self, other: Languages) -> Languages: ...
+    def __iadd__(self, other: Languages) -> Languages: ...
+    def diff(self, other: Languages) -> dict[LanguageType, LanguageDiff]:
+        """Changes going from these statistics to the ones in `other`.
+
+        Useful to compare a codebase before and after a refactor. The files are
+        matched by their path, so both objects should be obtained from the same paths.
+        Only the languages with any change are returned. The counts are summarised like
+        in `report_compact_plain`, including the code embedded in the files.
+
+        Parameters
+        ----------
+            other : Languages
+                Statistics to compare with, the new state of the code.
+
+        Examples
+        --------
+        ```python
+        >>> before.diff(after)
+        {LanguageType(Python): LanguageDiff(files: +1, code: +12, comments: +0, blanks: +3)}
+        >>> before.diff(after)[LanguageType("Python")].added
+        [FileDiff("./new_module.py", added, code: +12, comments: +0, blanks: +3)]
+        ```
+        """
+    def diff_plain(self, other: Languages) -> dict[str, dict[str, Any]]:
+        """The same method as `diff` but in python builtin objects, ready to be dumped as json.
+
+        Examples
+        --------
+        ```python
+        >>> before.diff_plain(after)
+        {'Python': {'files': 1, 'lines': 15, 'code': 12, 'comments': 0, 'blanks': 3,
+        'added': {'./new_module.py': {'lines': 15, 'code': 12, 'comments': 0, 'blanks': 3}},
+        'removed': {}, 'changed': {}}}
+        ```
+        """
 
 class Sort:
     """Used for sorting languages.
@@ -436,6 +1299,31 @@ class CodeStats:
     def plain(self) -> dict[str, int]:
         """Returns the content of the blob as a dict, blanks, code, comments and lines."""
     def __repr__(self) -> str: ...
+    def __add__(self, other: CodeStats) -> CodeStats:
+        """Adds the counts and the blobs, mirroring the `AddAssign` of tokei.
+
+        Also available as `+=`, and `sum` can be used on a list of `CodeStats`.
+
+        Examples
+        --------
+        ```python
+        >>> stats = report.stats
+        >>> stats + stats == sum([stats, stats])
+        True
+        ```
+        """
+    def __iadd__(self, other: CodeStats) -> CodeStats: ...
+    def __radd__(self, other: int) -> CodeStats:
+        """Allows using `sum` on a list of `CodeStats`."""
+    def __sub__(self, other: CodeStats) -> CodeStats:
+        """Subtracts the counts and the blobs of other.
+
+        Raises
+        ------
+            ValueError
+                If any of the counts would be negative.
+        """
+    def __eq__(self, other: object) -> bool: ...
 
 class Report:
     """A struct representing the statistics of a file.
@@ -462,8 +1350,234 @@ class Report:
     @property
     def stats(str) -> CodeStats:
         """The code statistics found in the file."""
+    @property
+    def is_generated(self) -> bool:
+        """Whether the file is generated code, like linguist detects it: with a header
+        marking it, like `Code generated ... DO NOT EDIT` or `@generated`, or with the
+        attribute `linguist-generated` in a `.gitattributes` file. The attributes are
+        only read for the files found walking the directories, not for the files of git
+        revisions and archives.
+        """
+    @property
+    def is_vendored(self) -> bool:
+        """Whether the file is vendored code, like linguist detects it: inside directories
+        like `vendor/`, `node_modules/` or `third_party/` below the path walked, or with
+        the attribute `linguist-vendored` in a `.gitattributes` file. The attributes are
+        only read for the files found walking the directories, not for the files of git
+        revisions and archives.
+        """
+    @property
+    def is_test(self) -> bool:
+        """Whether the file is test code, according to the default patterns and the ones
+        of `Config.test_patterns`. Matched against the path of the file relative to the
+        path walked, the repository path or the root of the archive.
+        """
     def __repr__(self) -> str: ...
+    def __add__(self, other: CodeStats) -> Report:
+        """Adds the stats to the ones of the file, like the `AddAssign<CodeStats>` of tokei.
+
+        Also available as `+=`.
+        """
+    def __iadd__(self, other: CodeStats) -> Report: ...
+    def __radd__(self, other: int) -> Language:
+        """Allows using `sum` on a list of reports, obtaining the `Language` containing them.
+
+        Examples
+        --------
+        ```python
+        >>> sum(langs[LanguageType("Python")].reports)
+        Language(empty: false)
+        ```
+        """
+    def __sub__(self, other: CodeStats) -> Report:
+        """Subtracts the stats from the ones of the file.
+
+        Raises
+        ------
+            ValueError
+                If any of the counts would be negative.
+        """
+    def __eq__(self, other: object) -> bool: ...
     def plain(self) -> dict[str, dict[str, int]]:
         """Representation of the object in builtin python objects, where the key corresponds
         to the filename that generated it, and the value is the result of `CodeStats.plain` method.
         """
+    def embedded(self) -> dict[LanguageType, CodeStats]:
+        """Statistics of the languages embedded in the file, like the code blocks of
+        markdown or the scripts of html, which `CodeStats.summarise` merges into the
+        counts of the file. The blocks nested inside them are counted in their own
+        language.
+
+        Examples
+        --------
+        ```python
+        >>> report = langs[LanguageType("Markdown")].reports[0]
+        >>> report.embedded()
+        {LanguageType(Python): CodeStats(blanks: 0, code: 14, comments: 0, lines: 14), LanguageType(BASH): CodeStats(blanks: 0, code: 1, comments: 0, lines: 1)}
+        ```
+        """
+
+class ReportsIterator(Iterator[tuple[LanguageType, Report]]):
+    """Iterator over the reports of the files found by `iter_reports`.
+
+    The files are parsed in parallel in the background while the iterator
+    is consumed, at most a few hundred reports are kept waiting in memory.
+    """
+
+    def __iter__(self) -> ReportsIterator: ...
+    def __next__(self) -> tuple[LanguageType, Report]: ...
+    @property
+    def inaccurate(self) -> bool:
+        """Whether a file found couldn't be read, so it has no report.
+
+        The same files mark their language as `Language.inaccurate` in
+        `Languages.get_statistics`. Only the files read so far are known, check it
+        once the iterator is exhausted.
+        """
+    def __repr__(self) -> str: ...
+
+def iter_reports(
+    paths: list[str], ignored: list[str], config: Config
+) -> ReportsIterator:
+    """Streams the statistics of each file as they are parsed.
+
+    Takes the same arguments as `Languages.get_statistics`, but instead of storing
+    every report, yields a tuple of `(LanguageType, Report)` per file as soon as the
+    file is parsed. The order of the files is not deterministic.
+    Files that can't be read are skipped, setting `ReportsIterator.inaccurate`.
+
+    Examples
+    --------
+    ```python
+    >>> from pytokei import Config, iter_reports
+    >>> for lang_type, report in iter_reports(["."], ["target"], Config()):
+    ...     print(lang_type, report.stats.code)
+    LanguageType(Rust) 139
+    ...
+    ```
+
+    Raises
+    ------
+        ValueError
+            If no path is given or the ignored patterns are not valid.
+    """
+
+class History(Iterator[tuple[str, int, Languages]]):
+    """Iterator over the statistics of the commits of a repository, returned by `history`.
+
+    The commits are counted lazily while iterating.
+    """
+
+    def __iter__(self) -> History: ...
+    def __next__(self) -> tuple[str, int, Languages]: ...
+    def __len__(self) -> int:
+        """Number of commits left."""
+    def __repr__(self) -> str: ...
+
+def history(
+    repo_path: str,
+    revs: Optional[list[str]] = None,
+    every_n: int = 1,
+    config: Optional[Config] = None,
+) -> History:
+    """Statistics of the commits of a local git repository, to see how the code grows.
+
+    Yields a tuple of `(commit hash, commit timestamp, Languages)` per commit, from the
+    oldest to the newest, following the first parent of the merge commits. The files are
+    read from the git objects as in `Languages.get_statistics_at_revision`, but the
+    statistics of the files are reused between commits, so only the files that
+    change in each commit are parsed. Requires `git` to be installed.
+
+    Parameters
+    ----------
+        repo_path : str
+            Path to the local git repository, or to a directory inside it to count
+            only the files below it.
+        revs : Optional[list[str]]
+            Revisions passed to `git rev-list`, like `["v0.1.0..main"]`. Defaults to `["HEAD"]`.
+        every_n : int
+            Count only one in every `n` commits. The last commit is always included.
+            Defaults to 1.
+        config : Optional[Config]
+            Config instance. Defaults to `Config()`.
+
+    Examples
+    --------
+    ```python
+    >>> from pytokei import history
+    >>> for commit, timestamp, langs in history(".", every_n=10):
+    ...     print(commit[:7], timestamp, langs.total_plain()["code"])
+    a4b7bc7 1792390000 3102
+    ...
+    ```
+
+    Raises
+    ------
+        ValueError
+            If the path is not a git repository, the revisions don't exist or `every_n` is 0.
+    """
+
+class FileDiff:
+    """Change of the statistics of a single file between two scans, obtained from `LanguageDiff`.
+
+    The counts are the difference between the new and the old values, so they
+    can be negative.
+    """
+
+    @property
+    def name(self) -> str:
+        """Filename that represents."""
+    @property
+    def status(self) -> str:
+        """One of `added`, `removed` or `changed`."""
+    @property
+    def blanks(self) -> int:
+        """Difference in the number of blank lines."""
+    @property
+    def code(self) -> int:
+        """Difference in the number of lines of code."""
+    @property
+    def comments(self) -> int:
+        """Difference in the number of comments."""
+    def lines(self) -> int:
+        """Difference in the total number of lines."""
+    def plain(self) -> dict[str, int]:
+        """Returns the differences as a dict, blanks, code, comments and lines."""
+    def __repr__(self) -> str: ...
+
+class LanguageDiff:
+    """Change of the statistics of a language between two scans, obtained from `Languages.diff`.
+
+    Files whose statistics didn't change are not included in `changed`.
+    """
+
+    @property
+    def blanks(self) -> int:
+        """Difference in the number of blank lines."""
+    @property
+    def code(self) -> int:
+        """Difference in the number of lines of code."""
+    @property
+    def comments(self) -> int:
+        """Difference in the number of comments."""
+    @property
+    def added(self) -> list[FileDiff]:
+        """Files only found in the new scan."""
+    @property
+    def removed(self) -> list[FileDiff]:
+        """Files only found in the old scan."""
+    @property
+    def changed(self) -> list[FileDiff]:
+        """Files found in both scans with different statistics."""
+    def lines(self) -> int:
+        """Difference in the total number of lines."""
+    def files(self) -> int:
+        """Difference in the number of files."""
+    def plain(self) -> dict[str, Any]:
+        """Representation of the object in builtin python objects.
+
+        Contains the differences of `files`, `lines`, `code`, `comments` and `blanks`,
+        and the files `added`, `removed` and `changed`, with the filename as key
+        and the result of `FileDiff.plain` as value.
+        """
+    def __repr__(self) -> str: ...
@@ -20,6 +20,8 @@ REPORT_TO_POSITION = {
     "blanks": 5,
 }
 
+OUTPUT_FORMATS = ("table", "markdown", "html")
+
 
 def _report_as_list(report: ReportType) -> List[Tuple[str, int, int, int, int, int]]:
     """Transform the report to simplify sorting.
@@ -128,11 +130,32 @@ def main(
     colored: bool = typer.Option(
         True, help="Whether to add color to the report or not."
     ),
+    output: str = typer.Option(
+        "table",
+        "-o",
+        "--output",
+        help=f"Format of the report. Must be one of {set(OUTPUT_FORMATS)}.",
+    ),
 ) -> None:  # pragma: no cover
     """Pytokei Command Line Interface."""
+    if output not in OUTPUT_FORMATS:
+        raise typer.BadParameter(
+            f"Must be one of {set(OUTPUT_FORMATS)}, got {output!r}.",
+            param_hint="--output",
+        )
+    if sort not in REPORT_TO_POSITION:
+        raise typer.BadParameter(
+            f"Must be one of {set(REPORT_TO_POSITION.keys())}, got {sort!r}.",
+            param_hint="--sort",
+        )
     langs = pytokei.Languages()
     conf = pytokei.Config()  # Just use the default for now
     langs.get_statistics([str(path)], ignore_paths.split(","), conf)
-    report = langs.report_compact_plain()
 
-    to_table(report, title=str(path), colored=colored, sort=sort)
+    if output == "markdown":
+        print(langs.to_markdown(sort=pytokei.Sort(sort)), end="")
+    elif output == "html":
+        print(langs.to_html(sort=pytokei.Sort(sort)), end="")
+    else:
+        report = langs.report_compact_plain()
+        to_table(report, title=str(path), colored=colored, sort=sort)
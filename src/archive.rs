@@ -0,0 +1,211 @@
+// Reading of the files of tar (optionally compressed with gzip) and zip archives,
+// to count them without extracting the archive to the disk.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use flate2::bufread::GzDecoder;
+use tar::EntryType;
+use tokei::{Config, LanguageType};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use crate::cache::Cache;
+use crate::git::ShebangDir;
+use crate::walk::{ReadFile, Selection};
+
+const ZIP_LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+const ZIP_END: &[u8] = b"PK\x05\x06";
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn unsupported_format() -> io::Error {
+    invalid_data("Unsupported archive format, expected a tar, tar.gz or zip file")
+}
+
+// The decoders report a corrupt archive, like a wrong checksum, with these kinds of error,
+// the rest of I/O errors are kept.
+fn decoder_error(e: io::Error) -> io::Error {
+    match e.kind() {
+        io::ErrorKind::Other | io::ErrorKind::InvalidInput => invalid_data(&e.to_string()),
+        _ => e,
+    }
+}
+
+// Path of an entry relative to the root of the archive, without `.` or `..` components.
+fn entry_path(name: &[u8]) -> PathBuf {
+    String::from_utf8_lossy(name)
+        .split(['/', '\\'])
+        .filter(|component| !matches!(*component, "" | "." | ".."))
+        .collect()
+}
+
+// Calls `f` with the path and the content of every regular file of the archive,
+// the format is detected from the content.
+pub fn for_each_file<F>(path: &Path, f: F) -> io::Result<()>
+where
+    F: FnMut(PathBuf, &mut dyn Read) -> io::Result<()>,
+{
+    let mut input = BufReader::new(File::open(path)?);
+    let mut magic = [0u8; 4];
+    let start = input.fill_buf()?;
+    let size = start.len().min(magic.len());
+    magic[..size].copy_from_slice(&start[..size]);
+
+    if size == 0 {
+        Err(unsupported_format())
+    } else if magic == ZIP_LOCAL_HEADER || magic == ZIP_END {
+        read_zip(input, f)
+    } else if magic[..2] == [0x1f, 0x8b] {
+        read_tar(GzDecoder::new(input), f)
+    } else {
+        read_tar(input, f)
+    }
+    .map_err(decoder_error)
+}
+
+fn read_tar<R: Read, F>(input: R, mut f: F) -> io::Result<()>
+where
+    F: FnMut(PathBuf, &mut dyn Read) -> io::Result<()>,
+{
+    let mut archive = tar::Archive::new(input);
+    let mut first = true;
+    for entry in archive.entries()? {
+        // A file that doesn't start with a valid header isn't a tar archive.
+        let mut entry = match entry {
+            Err(_) if first => return Err(unsupported_format()),
+            entry => entry?,
+        };
+        first = false;
+        // Directories, links and the rest of special entries are skipped.
+        if !matches!(
+            entry.header().entry_type(),
+            EntryType::Regular | EntryType::Continuous
+        ) {
+            continue;
+        }
+        // The name given by GNU long name or pax headers if there is one.
+        let name = entry_path(&entry.path_bytes());
+        f(name, &mut entry)?;
+    }
+    // Reads up to the end, so the checksum of a compressed archive is verified.
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
+    Ok(())
+}
+
+fn zip_error(e: ZipError) -> io::Error {
+    match e {
+        ZipError::Io(e) => e,
+        e => invalid_data(&format!("Invalid zip archive: {}", e)),
+    }
+}
+
+// The checksum of each file is verified after reading all of its content.
+fn read_zip<R: Read + Seek, F>(input: R, mut f: F) -> io::Result<()>
+where
+    F: FnMut(PathBuf, &mut dyn Read) -> io::Result<()>,
+{
+    let mut archive = ZipArchive::new(input).map_err(zip_error)?;
+    for i in 0..archive.len() {
+        let mut file = match archive.by_index(i) {
+            // Encrypted files and unknown compression methods are skipped.
+            Err(ZipError::UnsupportedArchive(_)) => continue,
+            file => file.map_err(zip_error)?,
+        };
+        let is_link = file
+            .unix_mode()
+            .map_or(false, |mode| mode & 0o170000 == 0o120000);
+        if file.is_dir() || is_link {
+            continue;
+        }
+        let name = entry_path(file.name_raw());
+        f(name, &mut file)?;
+    }
+    Ok(())
+}
+
+// Reports of the files of an archive with a known language, named with their path inside
+// the archive, and the languages of the files that couldn't be decoded. The entries are
+// read one by one and parsed in parallel.
+pub fn archive_reports(
+    path: &Path,
+    config: &Config,
+    selection: &Selection,
+    cache: Option<&Cache>,
+) -> io::Result<(Vec<ReadFile>, Vec<LanguageType>)> {
+    let shebangs = ShebangDir::new();
+    let hidden = config.hidden.unwrap_or(false);
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let (tx, rx) = crossbeam_channel::bounded::<(LanguageType, PathBuf, Vec<u8>)>(workers * 4);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let rx = rx.clone();
+                scope.spawn(move || {
+                    let mut files = Vec::new();
+                    let mut failed = Vec::new();
+                    for (language, name, content) in rx {
+                        match selection.parse(
+                            language,
+                            name.clone(),
+                            &name,
+                            &content,
+                            config,
+                            cache,
+                        ) {
+                            Ok(file) => files.push(file),
+                            Err(_) => failed.push(language),
+                        }
+                    }
+                    (files, failed)
+                })
+            })
+            .collect();
+        drop(rx);
+
+        let read = for_each_file(path, |name, data| {
+            let is_hidden = name
+                .iter()
+                .any(|component| component.to_string_lossy().starts_with('.'));
+            if (is_hidden && !hidden) || !selection.is_counted(&name) {
+                return Ok(());
+            }
+
+            // The content is only needed to detect the language when there is no extension.
+            let mut content = Vec::new();
+            let language = match (name.file_name(), name.extension()) {
+                (Some(_), Some(_)) => LanguageType::from_path(&name, config),
+                (Some(file_name), None) => {
+                    data.read_to_end(&mut content)?;
+                    shebangs.language(file_name, &content, config)
+                }
+                (None, _) => None,
+            };
+            let language = match (language, config.types.as_deref()) {
+                (Some(language), Some(types)) if !types.contains(&language) => None,
+                (language, _) => language,
+            };
+            if let Some(language) = language {
+                data.read_to_end(&mut content)?;
+                // The workers only stop receiving after the sender is dropped.
+                let _ = tx.send((language, name, content));
+            }
+            Ok(())
+        });
+        drop(tx);
+
+        let mut reports = (Vec::new(), Vec::new());
+        for handle in handles {
+            let (files, failed) = handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e));
+            reports.0.extend(files);
+            reports.1.extend(failed);
+        }
+        read.map(|_| reports)
+    })
+}
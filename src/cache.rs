@@ -0,0 +1,70 @@
+// On-disk cache of the statistics of each file, keyed by the hash of its content
+// so unchanged files are not parsed again, even across different runs.
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use sha1::{Digest, Sha1};
+use tokei::{CodeStats, Config, LanguageType};
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl Cache {
+    // Every option that changes how a file is counted goes into the fingerprint,
+    // so the entries obtained with different configurations don't mix.
+    pub fn new(dir: &Path, config: &Config) -> io::Result<Self> {
+        let fingerprint = format!(
+            "pytokei-{}-doc_strings-{:?}",
+            env!("CARGO_PKG_VERSION"),
+            config.treat_doc_strings_as_comments.unwrap_or(false)
+        );
+        let dir = dir.join(to_hex(&Sha1::digest(fingerprint.as_bytes())));
+        fs::create_dir_all(&dir)?;
+        Ok(Cache { dir })
+    }
+
+    fn entry(&self, language: LanguageType, text: &[u8]) -> PathBuf {
+        let mut hasher = Sha1::new();
+        hasher.update(language.name().as_bytes());
+        hasher.update([0]);
+        hasher.update(text);
+        let key = to_hex(&hasher.finalize());
+        self.dir.join(&key[..2]).join(key)
+    }
+
+    pub fn parse(&self, language: LanguageType, text: &[u8], config: &Config) -> CodeStats {
+        let entry = self.entry(language, text);
+
+        let cached = fs::read(&entry)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok());
+        if let Some(stats) = cached {
+            return stats;
+        }
+
+        let stats = language.parse_from_slice(text, config);
+        // A failure writing the cache only means the file will be parsed again next time.
+        let _ = self.store(&entry, &stats);
+        stats
+    }
+
+    fn store(&self, entry: &Path, stats: &CodeStats) -> io::Result<()> {
+        let parent = entry.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(parent)?;
+        // Written to a temporary file first so concurrent readers never see partial entries.
+        let tmp = entry.with_extension(format!(
+            "{}-{:?}.tmp",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec(stats)?)?;
+        fs::rename(tmp, entry)
+    }
+}
@@ -0,0 +1,110 @@
+// Owners of the files according to a CODEOWNERS file, with the rules of github and gitlab:
+// the last pattern matching a file gives its owners. The sections of gitlab, like
+// `[Backend] @backend-team`, are matched independently and their owners combined.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+// Group of the files without owners.
+pub const UNOWNED: &str = "unowned";
+// Directories where the CODEOWNERS file can be found, besides the root of the repository.
+const CODEOWNERS_DIRS: &[&str] = &[".github", ".gitlab", "docs"];
+
+struct Rule {
+    matcher: Gitignore,
+    owners: Vec<String>,
+}
+
+pub struct CodeOwners {
+    // Root of the repository, the patterns are relative to it.
+    pub root: PathBuf,
+    sections: Vec<Vec<Rule>>,
+}
+
+// Name and default owners of a gitlab section header, like `^[Docs][2] @docs-team`.
+fn section_owners(line: &str) -> Option<Vec<String>> {
+    let rest = line.strip_prefix('^').unwrap_or(line).strip_prefix('[')?;
+    let (_, mut rest) = rest.split_once(']')?;
+    // Number of approvals required.
+    if let Some(approvals) = rest.strip_prefix('[') {
+        rest = approvals.split_once(']')?.1;
+    }
+    Some(owners(rest.split_whitespace()))
+}
+
+fn owners<'a>(tokens: impl Iterator<Item = &'a str>) -> Vec<String> {
+    tokens
+        .take_while(|token| !token.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+impl CodeOwners {
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let root = match dir.file_name().and_then(|name| name.to_str()) {
+            Some(name) if CODEOWNERS_DIRS.contains(&name) => dir.parent().unwrap_or(dir),
+            _ => dir,
+        };
+        Self::parse(&content, root.to_path_buf())
+    }
+
+    pub fn parse(content: &str, root: PathBuf) -> io::Result<Self> {
+        let mut sections = vec![Vec::new()];
+        let mut default_owners = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(owners) = section_owners(line) {
+                sections.push(Vec::new());
+                default_owners = owners;
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+            let pattern = tokens.next().unwrap_or_default();
+            let mut owners = owners(tokens);
+            if owners.is_empty() {
+                owners = default_owners.clone();
+            }
+            let mut builder = GitignoreBuilder::new("");
+            builder
+                .add_line(None, pattern)
+                .and_then(|builder| builder.build())
+                .map(|matcher| {
+                    let rules = sections.last_mut().unwrap();
+                    rules.push(Rule { matcher, owners });
+                })
+                .map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid pattern at line {}: {}", number + 1, e),
+                    )
+                })?;
+        }
+        Ok(CodeOwners { root, sections })
+    }
+
+    // Owners of a file given by its path relative to the root, without duplicates.
+    pub fn owners(&self, relative: &Path) -> Vec<&str> {
+        let mut owners: Vec<&str> = Vec::new();
+        for rules in &self.sections {
+            let rule = rules.iter().rev().find(|rule| {
+                rule.matcher
+                    .matched_path_or_any_parents(relative, false)
+                    .is_ignore()
+            });
+            for owner in rule.into_iter().flat_map(|rule| &rule.owners) {
+                if !owners.contains(&owner.as_str()) {
+                    owners.push(owner);
+                }
+            }
+        }
+        owners
+    }
+}
@@ -0,0 +1,862 @@
+// Access to the objects of a local git repository through the plumbing commands
+// of git, to count the files of any revision without checking it out.
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+
+use tokei::{CodeStats, Config, LanguageType, Report};
+
+use crate::cache::Cache;
+use crate::lines::classify;
+use crate::tags::Tags;
+use crate::walk::{decode, parse_text, ReadFile, Selection, SkipReason};
+
+const GITIGNORE_FILE: &str = ".gitignore";
+const DOT_IGNORE_FILE: &str = ".ignore";
+const TOKEIGNORE_FILE: &str = ".tokeignore";
+
+pub struct Repository {
+    path: PathBuf,
+    // Relative path from `path` to the top directory of the working tree.
+    top: PathBuf,
+    // Relative path from the top directory to `path`, like `sub/`, only the files
+    // below it are counted.
+    prefix: PathBuf,
+}
+
+// A file tracked in a revision, with the id of the blob containing it.
+#[derive(Clone)]
+pub struct TreeFile {
+    pub path: PathBuf,
+    pub oid: String,
+}
+
+// A file modified between two revisions, None in the side where it isn't a regular file.
+pub struct FileChange {
+    pub old: Option<TreeFile>,
+    pub new: Option<TreeFile>,
+}
+
+fn is_regular_file(mode: &str) -> bool {
+    matches!(mode, "100644" | "100755")
+}
+
+// Line ranges of a hunk header like `@@ -12,3 +12,4 @@`, as (start, count) with the
+// start counted from 1.
+fn hunk_ranges(header: &str) -> Option<((usize, usize), (usize, usize))> {
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let mut parts = header.strip_prefix("@@ ")?.split(' ');
+    let old = range(parts.next()?.strip_prefix('-')?)?;
+    let new = range(parts.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+impl Repository {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut repo = Repository {
+            path: path.to_path_buf(),
+            top: PathBuf::new(),
+            prefix: PathBuf::new(),
+        };
+        // Fails too if the path isn't inside a repository.
+        let output = repo.git(["rev-parse", "--show-cdup", "--show-prefix"])?;
+        let output = String::from_utf8_lossy(&output);
+        let mut lines = output.lines();
+        repo.top = PathBuf::from(lines.next().unwrap_or_default());
+        repo.prefix = PathBuf::from(lines.next().unwrap_or_default());
+        Ok(repo)
+    }
+
+    // Whether a file, given by its path from the top directory, is below the repository path.
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.prefix)
+    }
+
+    // Path of a file relative to the repository path, from its path from the top directory.
+    pub fn relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.prefix).unwrap_or(path)
+    }
+
+    // Path of a file of the repository as it would be found walking the repository path,
+    // from its path from the top directory.
+    pub fn report_name(&self, path: &Path) -> PathBuf {
+        self.path.join(self.relative_path(path))
+    }
+
+    // Pathspecs limiting a command to the files below the repository path, and to the
+    // ignore files of the directories above it, which apply to them too.
+    fn pathspecs(&self) -> Vec<OsString> {
+        if self.prefix.as_os_str().is_empty() {
+            return Vec::new();
+        }
+        let literal = |path: &Path| {
+            let mut pathspec = OsString::from(":(top,literal)");
+            pathspec.push(path);
+            pathspec
+        };
+        let mut pathspecs = vec![OsString::from("--"), literal(&self.prefix)];
+        for dir in self.prefix.ancestors().skip(1) {
+            for name in [GITIGNORE_FILE, DOT_IGNORE_FILE, TOKEIGNORE_FILE] {
+                pathspecs.push(literal(&dir.join(name)));
+            }
+        }
+        pathspecs
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.path);
+        command
+    }
+
+    pub fn git<I, S>(&self, args: I) -> io::Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = self.command().args(args).output()?;
+        if !output.status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(output.stdout)
+    }
+
+    // Full id of the commit a revision (branch, tag, hash...) points to.
+    pub fn resolve(&self, rev: &str) -> io::Result<String> {
+        let output = self
+            .git([
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", rev),
+            ])
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Revision not found: {}", rev),
+                )
+            })?;
+        Ok(String::from_utf8_lossy(&output).trim().to_string())
+    }
+
+    // Commits reachable from the revisions following the first parents, oldest first,
+    // with their commit time.
+    pub fn commits(&self, revs: &[String]) -> io::Result<Vec<(String, i64)>> {
+        let mut args = vec!["rev-list", "--first-parent", "--reverse", "--timestamp"];
+        args.extend(revs.iter().map(String::as_str));
+        args.push("--");
+        let output = self.git(args)?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|line| {
+                let (timestamp, commit) = line.split_once(' ')?;
+                Some((commit.to_string(), timestamp.parse().ok()?))
+            })
+            .collect())
+    }
+
+    // Regular files tracked in a commit, symbolic links and submodules are skipped
+    // as the directory walker does.
+    pub fn tree(&self, commit: &str) -> io::Result<Vec<TreeFile>> {
+        let mut args: Vec<OsString> = ["ls-tree", "-r", "-z", "--full-tree", commit]
+            .into_iter()
+            .map(OsString::from)
+            .collect();
+        args.extend(self.pathspecs());
+        let output = self.git(args)?;
+        let mut files = Vec::new();
+        for entry in output.split(|b| *b == 0).filter(|e| !e.is_empty()) {
+            let entry = String::from_utf8_lossy(entry);
+            let (info, path) = match entry.split_once('\t') {
+                Some(split) => split,
+                None => continue,
+            };
+            let mut info = info.split(' ');
+            match (info.next(), info.next(), info.next()) {
+                (Some(mode), Some("blob"), Some(oid)) if is_regular_file(mode) => {
+                    files.push(TreeFile {
+                        path: PathBuf::from(path),
+                        oid: oid.to_string(),
+                    })
+                }
+                _ => continue,
+            }
+        }
+        Ok(files)
+    }
+
+    // Files changed between two commits, detecting the renamed ones.
+    pub fn changes(&self, base: &str, head: &str) -> io::Result<Vec<FileChange>> {
+        let mut args: Vec<OsString> = ["diff", "--raw", "-z", "-M", "--no-abbrev", base, head]
+            .into_iter()
+            .map(OsString::from)
+            .collect();
+        args.extend(self.pathspecs());
+        let output = self.git(args)?;
+        let mut fields = output
+            .split(|b| *b == 0)
+            .map(|field| String::from_utf8_lossy(field).to_string());
+
+        let mut changes = Vec::new();
+        // Each change is `:<old mode> <new mode> <old oid> <new oid> <status>` followed
+        // by the path, or by the old and new paths for renames and copies.
+        while let Some(header) = fields.next() {
+            let info: Vec<&str> = match header.strip_prefix(':') {
+                Some(info) => info.split(' ').collect(),
+                None => continue,
+            };
+            if info.len() < 5 {
+                continue;
+            }
+            let old_path = fields.next().unwrap_or_default();
+            let new_path = match info[4].chars().next() {
+                Some('R' | 'C') => fields.next().unwrap_or_default(),
+                _ => old_path.clone(),
+            };
+            let file = |mode: &str, oid: &str, path: String| {
+                is_regular_file(mode).then(|| TreeFile {
+                    path: PathBuf::from(path),
+                    oid: oid.to_string(),
+                })
+            };
+            changes.push(FileChange {
+                old: file(info[0], info[2], old_path),
+                new: file(info[1], info[3], new_path),
+            });
+        }
+        Ok(changes)
+    }
+
+    // Indexes (starting at 0) of the lines removed from the old blob and added to the new one.
+    pub fn changed_lines(&self, old: &str, new: &str) -> io::Result<(Vec<usize>, Vec<usize>)> {
+        let output = self.git([
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-textconv",
+            old,
+            new,
+        ])?;
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        for line in String::from_utf8_lossy(&output).lines() {
+            if let Some(((old_start, old_count), (new_start, new_count))) = hunk_ranges(line) {
+                removed.extend((old_start..old_start + old_count).map(|l| l - 1));
+                added.extend((new_start..new_start + new_count).map(|l| l - 1));
+            }
+        }
+        Ok((removed, added))
+    }
+
+    // Author of each line of a file in a commit, with the path from the top directory.
+    pub fn authors(&self, commit: &str, path: &Path) -> io::Result<Vec<String>> {
+        let mut args = vec![
+            OsString::from("blame"),
+            "--line-porcelain".into(),
+            commit.into(),
+        ];
+        args.push("--".into());
+        args.push(self.top.join(path).into_os_string());
+        let output = self.git(args)?;
+
+        // Each line is preceded by the headers of the commit it comes from, the content
+        // is the only line starting with a tab.
+        let mut authors = Vec::new();
+        let mut author = "";
+        for line in output.split(|b| *b == b'\n') {
+            if let Some(name) = line.strip_prefix(b"author ") {
+                author = std::str::from_utf8(name).unwrap_or_default();
+            } else if line.starts_with(b"\t") {
+                authors.push(author.to_string());
+            }
+        }
+        Ok(authors)
+    }
+
+    pub fn blobs(&self) -> io::Result<BlobReader> {
+        let mut child = self
+            .command()
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().map(BufReader::new);
+        match (stdin, stdout) {
+            (Some(stdin), Some(stdout)) => Ok(BlobReader {
+                child,
+                stdin: Some(stdin),
+                stdout,
+            }),
+            _ => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "Couldn't communicate with git cat-file",
+            )),
+        }
+    }
+}
+
+// Reads the content of the blobs from a single `git cat-file --batch` process.
+pub struct BlobReader {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    pub fn read(&mut self, oid: &str) -> io::Result<Vec<u8>> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        writeln!(stdin, "{}", oid)?;
+        stdin.flush()?;
+
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        // The header is `<oid> <type> <size>`, or `<oid> missing`.
+        let size = match header.trim_end().rsplit_once(' ') {
+            Some((_, size)) => size.parse::<usize>().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Object not found: {}", oid),
+                )
+            })?,
+            None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        };
+
+        let mut content = vec![0; size];
+        self.stdout.read_exact(&mut content)?;
+        // Every object is followed by a newline.
+        self.stdout.read_exact(&mut [0])?;
+        Ok(content)
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        // Closing stdin makes git exit.
+        self.stdin.take();
+        let _ = self.child.wait();
+    }
+}
+
+// The ignore files found in a revision, by the directory containing them.
+pub struct IgnoreRules {
+    matchers: BTreeMap<PathBuf, Vec<Gitignore>>,
+    hidden: bool,
+    // Directory walked, the hidden directories containing it don't exclude its files.
+    root: PathBuf,
+}
+
+impl IgnoreRules {
+    pub fn new(
+        files: &[TreeFile],
+        blobs: &mut BlobReader,
+        config: &Config,
+        root: &Path,
+    ) -> io::Result<Self> {
+        let ignore = config.no_ignore.map(|b| !b).unwrap_or(true);
+        let ignore_dot = ignore && config.no_ignore_dot.map(|b| !b).unwrap_or(true);
+        let ignore_vcs = ignore && config.no_ignore_vcs.map(|b| !b).unwrap_or(true);
+        // In the same directory, the rules of the first file take precedence.
+        let mut names = Vec::new();
+        if ignore_dot {
+            names.extend([TOKEIGNORE_FILE, DOT_IGNORE_FILE]);
+        }
+        if ignore_vcs {
+            names.push(GITIGNORE_FILE);
+        }
+
+        let mut found: Vec<(usize, &TreeFile)> = files
+            .iter()
+            .filter_map(|file| {
+                let name = file.path.file_name()?.to_str()?;
+                names.iter().position(|n| *n == name).map(|i| (i, file))
+            })
+            .collect();
+        found.sort_by_key(|(precedence, _)| *precedence);
+
+        let mut matchers: BTreeMap<PathBuf, Vec<Gitignore>> = BTreeMap::new();
+        for (_, file) in found {
+            let dir = file.path.parent().unwrap_or_else(|| Path::new(""));
+            let content = blobs.read(&file.oid)?;
+            let mut builder = GitignoreBuilder::new(dir);
+            for line in String::from_utf8_lossy(&content).lines() {
+                // Invalid patterns are skipped, like git does.
+                let _ = builder.add_line(Some(file.path.clone()), line);
+            }
+            if let Ok(matcher) = builder.build() {
+                matchers.entry(dir.to_path_buf()).or_default().push(matcher);
+            }
+        }
+
+        Ok(IgnoreRules {
+            matchers,
+            hidden: config.hidden.unwrap_or(false),
+            root: root.to_path_buf(),
+        })
+    }
+
+    // The rules of the deepest directory with a match decide, as in the directory walker.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let hidden = path
+            .strip_prefix(&self.root)
+            .unwrap_or(path)
+            .iter()
+            .any(|component| component.to_string_lossy().starts_with('.'));
+        if hidden && !self.hidden {
+            return true;
+        }
+
+        for dir in path.ancestors().skip(1) {
+            for matcher in self.matchers.get(dir).into_iter().flatten() {
+                match matcher.matched_path_or_any_parents(path, false) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        false
+    }
+}
+
+// Directory for the files written to detect the language from the shebang,
+// which tokei only reads from the disk. Removed when dropped.
+pub struct ShebangDir {
+    dir: PathBuf,
+}
+
+impl ShebangDir {
+    pub fn new() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "pytokei-shebang-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        ShebangDir { dir }
+    }
+
+    pub fn language(&self, name: &OsStr, content: &[u8], config: &Config) -> Option<LanguageType> {
+        let first_line = content.split(|b| *b == b'\n').next().unwrap_or_default();
+        fs::create_dir_all(&self.dir).ok()?;
+        let path = self.dir.join(name);
+        fs::write(&path, first_line).ok()?;
+        let language = LanguageType::from_path(&path, config);
+        let _ = fs::remove_file(path);
+        language
+    }
+}
+
+impl Default for ShebangDir {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ShebangDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+// Counts the files of revisions of a repository. The results of every blob are
+// kept, so scanning several revisions only parses the files that changed.
+pub struct RevisionReader {
+    repo: Repository,
+    blobs: BlobReader,
+    shebangs: ShebangDir,
+    languages: HashMap<(String, OsString), Option<LanguageType>>,
+    // Stats of the blobs already parsed, and whether they have a generated header.
+    stats: HashMap<(String, LanguageType), (Result<CodeStats, SkipReason>, bool)>,
+}
+
+impl RevisionReader {
+    pub fn new(repo: Repository) -> io::Result<Self> {
+        Ok(RevisionReader {
+            blobs: repo.blobs()?,
+            repo,
+            shebangs: ShebangDir::new(),
+            languages: HashMap::new(),
+            stats: HashMap::new(),
+        })
+    }
+
+    pub fn repo(&self) -> &Repository {
+        &self.repo
+    }
+
+    // Language of a file of the revision, reading its content only when it has no extension.
+    pub fn file_language(
+        &mut self,
+        file: &TreeFile,
+        config: &Config,
+    ) -> io::Result<Option<LanguageType>> {
+        let language = match (file.path.file_name(), file.path.extension()) {
+            (Some(_), Some(_)) => LanguageType::from_path(&file.path, config),
+            (Some(name), None) => {
+                let key = (file.oid.clone(), name.to_os_string());
+                match self.languages.get(&key) {
+                    Some(language) => *language,
+                    None => {
+                        let content = self.blobs.read(&file.oid)?;
+                        let language = self.shebangs.language(name, &content, config);
+                        self.languages.insert(key, language);
+                        language
+                    }
+                }
+            }
+            (None, _) => None,
+        };
+        Ok(match (language, config.types.as_deref()) {
+            (Some(language), Some(types)) if !types.contains(&language) => None,
+            (language, _) => language,
+        })
+    }
+
+    fn ignore_rules(&mut self, commit: &str, config: &Config) -> io::Result<IgnoreRules> {
+        let files = self.repo.tree(commit)?;
+        IgnoreRules::new(&files, &mut self.blobs, config, &self.repo.prefix)
+    }
+
+    // Whether a file of a commit is below the repository path and isn't excluded.
+    fn is_counted(&self, file: &TreeFile, rules: &IgnoreRules, selection: &Selection) -> bool {
+        self.repo.contains(&file.path)
+            && !rules.is_ignored(&file.path)
+            && selection.is_counted(self.repo.relative_path(&file.path))
+    }
+
+    // Files of the commit to be counted, with their language.
+    fn files(
+        &mut self,
+        commit: &str,
+        config: &Config,
+        selection: &Selection,
+    ) -> io::Result<Vec<(LanguageType, TreeFile)>> {
+        let files = self.repo.tree(commit)?;
+        let rules = IgnoreRules::new(&files, &mut self.blobs, config, &self.repo.prefix)?;
+
+        let mut counted = Vec::new();
+        for file in files {
+            if !self.is_counted(&file, &rules, selection) {
+                continue;
+            }
+            if let Some(language) = self.file_language(&file, config)? {
+                counted.push((language, file));
+            }
+        }
+        Ok(counted)
+    }
+
+    // Reports of the files of a commit, the blobs not seen before are read in order
+    // and parsed in parallel.
+    pub fn reports(
+        &mut self,
+        commit: &str,
+        config: &Config,
+        selection: &Selection,
+        cache: Option<&Cache>,
+    ) -> io::Result<Vec<ReadFile>> {
+        let (known, new): (Vec<_>, Vec<_>) = self
+            .files(commit, config, selection)?
+            .into_iter()
+            .partition(|(language, file)| self.stats.contains_key(&(file.oid.clone(), *language)));
+
+        let mut reports: Vec<ReadFile> = known
+            .into_iter()
+            .map(|(language, file)| {
+                let name = self.repo.report_name(&file.path);
+                let (stats, generated) = &self.stats[&(file.oid, language)];
+                let relative = self.repo.relative_path(&file.path);
+                let tags = match stats {
+                    Ok(_) => selection.path_tags(relative, *generated, language),
+                    Err(_) => Tags::default(),
+                };
+                let result = stats.clone().map(|stats| {
+                    let mut report = Report::new(name.clone());
+                    report += stats;
+                    report
+                });
+                ReadFile {
+                    language,
+                    name,
+                    result,
+                    tags,
+                }
+            })
+            .collect();
+
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let (tx, rx) = crossbeam_channel::bounded::<(LanguageType, TreeFile, Vec<u8>)>(workers * 4);
+        let (report_tx, report_rx) = crossbeam_channel::unbounded();
+        let repo = &self.repo;
+        let blobs = &mut self.blobs;
+
+        thread::scope(|scope| -> io::Result<()> {
+            for _ in 0..workers {
+                let rx = rx.clone();
+                let report_tx = report_tx.clone();
+                scope.spawn(move || {
+                    for (language, file, content) in rx {
+                        let name = repo.report_name(&file.path);
+                        let relative = repo.relative_path(&file.path);
+                        let read =
+                            selection.parse(language, name, relative, &content, config, cache);
+                        if let Ok(read) = read {
+                            let _ = report_tx.send((file.oid, read));
+                        }
+                    }
+                });
+            }
+            drop(report_tx);
+
+            for (language, file) in new {
+                let content = blobs.read(&file.oid)?;
+                // The workers only stop receiving after the sender is dropped.
+                let _ = tx.send((language, file, content));
+            }
+            drop(tx);
+            Ok(())
+        })?;
+
+        for (oid, read) in report_rx {
+            let stats = read.result.as_ref().map(|report| report.stats.clone());
+            let stats = (stats.map_err(|reason| *reason), read.tags.generated);
+            self.stats.insert((oid, read.language), stats);
+            reports.push(read);
+        }
+        Ok(reports)
+    }
+
+    // Lines of the files of a commit attributed to the author that last changed them.
+    pub fn authors(
+        &mut self,
+        commit: &str,
+        config: &Config,
+        selection: &Selection,
+        cache: Option<&Cache>,
+    ) -> io::Result<AuthorStats> {
+        let files = self.files(commit, config, selection)?;
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let (tx, rx) = crossbeam_channel::bounded::<(LanguageType, TreeFile, Vec<u8>)>(workers * 4);
+        let repo = &self.repo;
+        let blobs = &mut self.blobs;
+
+        let results = thread::scope(|scope| -> io::Result<Vec<AuthorStats>> {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    let rx = rx.clone();
+                    scope.spawn(move || -> io::Result<AuthorStats> {
+                        let mut stats = AuthorStats::default();
+                        for (language, file, content) in rx {
+                            let name = repo.report_name(&file.path);
+                            let relative = repo.relative_path(&file.path);
+                            let read = selection
+                                .parse(language, name, relative, &content, config, cache)?;
+                            // The lines of the files skipped aren't attributed.
+                            if read.result.is_ok() {
+                                let authors = repo.authors(commit, &file.path)?;
+                                let text = decode(&content)?;
+                                let kinds = classify(language, &text, config);
+                                // The lines tokei doesn't count aren't attributed either.
+                                for (author, kind) in authors.into_iter().zip(kinds) {
+                                    if let Some(kind) = kind {
+                                        let languages = stats.authors.entry(author).or_default();
+                                        kind.count(languages.entry(language).or_default());
+                                    }
+                                }
+                            }
+                            stats.reports.push(read);
+                        }
+                        Ok(stats)
+                    })
+                })
+                .collect();
+            // Sending fails when every worker stopped after an error.
+            drop(rx);
+
+            for (language, file) in files {
+                let content = blobs.read(&file.oid)?;
+                if tx.send((language, file, content)).is_err() {
+                    break;
+                }
+            }
+            drop(tx);
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        })?;
+
+        let mut stats = AuthorStats::default();
+        for result in results {
+            for (author, languages) in result.authors {
+                let totals = stats.authors.entry(author).or_default();
+                for (language, counts) in languages {
+                    let total = totals.entry(language).or_default();
+                    total.blanks += counts.blanks;
+                    total.code += counts.code;
+                    total.comments += counts.comments;
+                }
+            }
+            stats.reports.extend(result.reports);
+        }
+        Ok(stats)
+    }
+
+    // Language and content of a file changed in a revision, if it has to be counted.
+    fn changed_file(
+        &mut self,
+        file: Option<TreeFile>,
+        rules: &IgnoreRules,
+        config: &Config,
+        selection: &Selection,
+    ) -> io::Result<Option<ChangedFile>> {
+        let file = match file {
+            Some(file) if self.is_counted(&file, rules, selection) => file,
+            _ => return Ok(None),
+        };
+        match self.file_language(&file, config)? {
+            Some(language) => {
+                let content = self.blobs.read(&file.oid)?;
+                let text = decode(&content)?;
+                let text = match selection.check(&content, &text) {
+                    Some(reason) => Err(reason),
+                    None => Ok(text),
+                };
+                Ok(Some((language, file, text)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Lines added and removed per language between two commits, classified as tokei does,
+    // and the reports of the files changed as they are in the head commit.
+    pub fn diff(
+        &mut self,
+        base: &str,
+        head: &str,
+        config: &Config,
+        selection: &Selection,
+        cache: Option<&Cache>,
+    ) -> io::Result<DiffStats> {
+        let base_rules = self.ignore_rules(base, config)?;
+        let head_rules = self.ignore_rules(head, config)?;
+        let mut diff = DiffStats::default();
+
+        for change in self.repo.changes(base, head)? {
+            // The files skipped count as if they weren't there, those of head are reported.
+            let old = self
+                .changed_file(change.old, &base_rules, config, selection)?
+                .and_then(|(language, file, text)| Some((language, file, text.ok()?)));
+            let new = match self.changed_file(change.new, &head_rules, config, selection)? {
+                Some((language, file, Err(reason))) => {
+                    diff.reports.push(ReadFile {
+                        language,
+                        name: self.repo.report_name(&file.path),
+                        result: Err(reason),
+                        tags: Tags::default(),
+                    });
+                    None
+                }
+                Some((language, file, Ok(text))) => Some((language, file, text)),
+                None => None,
+            };
+
+            // Only the lines changed are classified when the language is kept,
+            // otherwise the whole file is added or removed.
+            let (removed, added) = match (&old, &new) {
+                (Some((old_language, old, _)), Some((new_language, new, _)))
+                    if old_language == new_language =>
+                {
+                    let (removed, added) = self.repo.changed_lines(&old.oid, &new.oid)?;
+                    (Some(removed), Some(added))
+                }
+                _ => (None, None),
+            };
+
+            if let Some((language, _, content)) = &old {
+                let stats = &mut diff.languages.entry(*language).or_default().1;
+                count_lines_of(*language, content, config, removed.as_deref(), stats);
+            }
+            if let Some((language, file, content)) = new {
+                let stats = &mut diff.languages.entry(language).or_default().0;
+                count_lines_of(language, &content, config, added.as_deref(), stats);
+                let name = self.repo.report_name(&file.path);
+                let relative = self.repo.relative_path(&file.path);
+                diff.reports.push(ReadFile {
+                    language,
+                    name: name.clone(),
+                    result: Ok(parse_text(language, name, &content, config, cache)?),
+                    tags: selection.tags(relative, &content, language),
+                });
+            }
+        }
+        Ok(diff)
+    }
+}
+
+// Lines of each author per language, and the reports of the files of the commit.
+#[derive(Default)]
+pub struct AuthorStats {
+    pub authors: BTreeMap<String, BTreeMap<LanguageType, CodeStats>>,
+    pub reports: Vec<ReadFile>,
+}
+
+// A file changed between two commits, with its content decoded or why it's skipped.
+type ChangedFile = (LanguageType, TreeFile, Result<Vec<u8>, SkipReason>);
+
+// Lines (added, removed) per language, and the reports of the files in the head commit.
+#[derive(Default)]
+pub struct DiffStats {
+    pub languages: BTreeMap<LanguageType, (CodeStats, CodeStats)>,
+    pub reports: Vec<ReadFile>,
+}
+
+// Adds the counts of the given lines of the text, or of all of them.
+fn count_lines_of(
+    language: LanguageType,
+    text: &[u8],
+    config: &Config,
+    lines: Option<&[usize]>,
+    stats: &mut CodeStats,
+) {
+    match lines {
+        Some(lines) => {
+            // The whole text is classified once, the kinds of the lines are taken from it.
+            let kinds = classify(language, text, config);
+            for kind in lines.iter().filter_map(|line| *kinds.get(*line)?) {
+                kind.count(stats);
+            }
+        }
+        None => {
+            let summary = language.parse_from_slice(text, config).summarise();
+            stats.blanks += summary.blanks;
+            stats.code += summary.code;
+            stats.comments += summary.comments;
+        }
+    }
+}
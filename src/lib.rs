@@ -1,18 +1,38 @@
+// The binary operators generated by the pyo3 macros define impl blocks inside functions.
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
 
 use crate::pyconfig::PyConfig;
+use crate::pydiff::{PyFileDiff, PyLanguageDiff};
+use crate::pyhistory::{history, PyHistory};
+use crate::pyiter::{iter_reports, PyReportsIterator};
 use crate::pylanguage::PyLanguage;
 use crate::pylanguage_type::PyLanguageType;
 use crate::pylanguages::PyLanguages;
 use crate::pysort::{sort_types, PySort};
 use crate::pystats::{PyCodeStats, PyReport};
 
+pub mod archive;
+pub mod cache;
+pub mod codeowners;
+pub mod git;
+pub mod lines;
 pub mod pyconfig;
+pub mod pydiff;
+pub mod pyhistory;
+pub mod pyiter;
 pub mod pylanguage;
 pub mod pylanguage_type;
 pub mod pylanguages;
 pub mod pysort;
 pub mod pystats;
+pub mod render;
+pub mod table;
+pub mod tags;
+pub mod walk;
+#[cfg(target_os = "linux")]
+pub mod watch;
 
 #[pymodule]
 fn _pytokei(_py: Python, m: &PyModule) -> PyResult<()> {
@@ -26,5 +46,11 @@ fn _pytokei(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyReport>().unwrap();
     m.add_class::<PyLanguageType>().unwrap();
     m.add_class::<PyLanguage>().unwrap();
+    m.add_class::<PyReportsIterator>().unwrap();
+    m.add_class::<PyLanguageDiff>().unwrap();
+    m.add_class::<PyFileDiff>().unwrap();
+    m.add_function(wrap_pyfunction!(iter_reports, m)?)?;
+    m.add_class::<PyHistory>().unwrap();
+    m.add_function(wrap_pyfunction!(history, m)?)?;
     Ok(())
 }
@@ -0,0 +1,565 @@
+// Classification of single lines. tokei only gives the totals of a text, so the lines
+// are read here the same way its `SyntaxCounter` does, in a single pass keeping the open
+// quote, the stack of comments and the embedded blocks, and each line takes the kind and
+// the language tokei counts it as.
+use std::ops::Range;
+use std::str::FromStr;
+
+use regex::bytes::Regex;
+use tokei::{CodeStats, Config, LanguageType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Code,
+    Comment,
+}
+
+impl LineKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            LineKind::Blank => "blank",
+            LineKind::Code => "code",
+            LineKind::Comment => "comment",
+        }
+    }
+
+    // Adds the line to the corresponding count.
+    pub fn count(self, stats: &mut CodeStats) {
+        match self {
+            LineKind::Blank => stats.blanks += 1,
+            LineKind::Code => stats.code += 1,
+            LineKind::Comment => stats.comments += 1,
+        }
+    }
+}
+
+// Byte offset where each line ends, including the newline.
+fn line_ends(text: &[u8]) -> Vec<usize> {
+    let mut ends: Vec<usize> = text
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == b'\n')
+        .map(|(i, _)| i + 1)
+        .collect();
+    if ends.last().copied().unwrap_or(0) < text.len() {
+        ends.push(text.len());
+    }
+    ends
+}
+
+// Line counted by tokei: offset of the text where it is read, kind and embedded language,
+// None being the language of the text.
+type Event = (usize, LineKind, Option<LanguageType>);
+
+fn is_whitespace(byte: u8) -> bool {
+    byte == b' ' || (b'\x09'..=b'\x0d').contains(&byte)
+}
+
+fn trim_range(text: &[u8]) -> Range<usize> {
+    match text.iter().position(|b| !is_whitespace(*b)) {
+        Some(start) => {
+            let end = text
+                .iter()
+                .rposition(|b| !is_whitespace(*b))
+                .unwrap_or(start);
+            start..end + 1
+        }
+        None => 0..0,
+    }
+}
+
+fn trim(text: &[u8]) -> &[u8] {
+    &text[trim_range(text)]
+}
+
+fn trim_start(text: &[u8]) -> &[u8] {
+    let start = text.iter().position(|b| !is_whitespace(*b));
+    &text[start.unwrap_or(text.len())..]
+}
+
+fn contains(text: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && text.windows(needle.len()).any(|window| window == needle)
+}
+
+// Contents of an embedded html block parsed by tokei, without the whitespace around the
+// first and last newlines.
+fn block_range(text: &[u8]) -> Range<usize> {
+    let is_kept = |byte: &u8| *byte == b'\n' || !is_whitespace(*byte);
+    let start = text
+        .iter()
+        .position(is_kept)
+        .map_or(0, |i| (i + 1).min(text.len().saturating_sub(1)));
+    let end =
+        text.iter()
+            .rposition(is_kept)
+            .map_or(text.len(), |i| match text[i.saturating_sub(1)] {
+                b'\r' => i - 1,
+                _ => i,
+            });
+    match start < text.len() {
+        true => start..(end + 1).clamp(start, text.len()),
+        false => 0..0,
+    }
+}
+
+fn line_end(text: &[u8], start: usize) -> usize {
+    text[start..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(text.len(), |i| start + i + 1)
+}
+
+// Lines of an embedded block, moved to the offsets of the outer text.
+fn embedded(
+    language: LanguageType,
+    text: &[u8],
+    config: &Config,
+    blocks: &Blocks,
+    offset: impl Fn(usize) -> usize,
+) -> impl Iterator<Item = Event> {
+    events(language, text, config, blocks)
+        .into_iter()
+        .map(move |(at, kind, child)| (offset(at), kind, child.or(Some(language))))
+}
+
+// Opening and closing tags of the blocks embedded in html, the language given in the
+// opening one and the language used by default.
+type HtmlBlock = (Regex, Regex, fn(&str) -> Option<LanguageType>, LanguageType);
+
+// Regexes finding the embedded blocks, built once for each text classified.
+struct Blocks {
+    html: [HtmlBlock; 3],
+    // Opening and closing fences of the markdown code blocks.
+    fences: (Regex, Regex),
+}
+
+impl Blocks {
+    fn new() -> Self {
+        let regex = |pattern: &str| Regex::new(pattern).expect("valid regex");
+        let block = |opening: &str, closing: &str, language, default| -> HtmlBlock {
+            (regex(opening), regex(closing), language, default)
+        };
+        let from_name: fn(&str) -> Option<LanguageType> =
+            |name| LanguageType::from_str(&name.to_lowercase()).ok();
+        Blocks {
+            html: [
+                block(
+                    r#"^<script(?:.*type="(.*)")?.*?>"#,
+                    "</script>",
+                    LanguageType::from_mime,
+                    LanguageType::JavaScript,
+                ),
+                block(
+                    r#"^<style(?:.*lang="(.*)")?.*?>"#,
+                    "</style>",
+                    from_name,
+                    LanguageType::Css,
+                ),
+                block(
+                    r#"^<template(?:.*lang="(.*)")?.*?>"#,
+                    "</template>",
+                    from_name,
+                    LanguageType::Html,
+                ),
+            ],
+            fences: (regex(r"^```\S+\s"), regex(r"```\s?")),
+        }
+    }
+}
+
+// State of the text while it is read: the quote or the comments still open.
+struct Syntax {
+    language: LanguageType,
+    quote: Option<&'static str>,
+    quote_is_doc_quote: bool,
+    quote_is_verbatim: bool,
+    stack: Vec<&'static str>,
+}
+
+impl Syntax {
+    fn new(language: LanguageType) -> Self {
+        Syntax {
+            language,
+            quote: None,
+            quote_is_doc_quote: false,
+            quote_is_verbatim: false,
+            stack: Vec::new(),
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        self.quote.is_none() && self.stack.is_empty()
+    }
+
+    fn is_fortran(&self) -> bool {
+        matches!(
+            self.language,
+            LanguageType::FortranLegacy | LanguageType::FortranModern
+        )
+    }
+
+    fn comments(&self) -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+        let language = self.language;
+        language
+            .multi_line_comments()
+            .iter()
+            .chain(language.nested_comments())
+    }
+
+    fn starts_with_line_comment(&self, line: &[u8]) -> bool {
+        let comments = self.language.line_comments();
+        comments.iter().any(|c| line.starts_with(c.as_bytes()))
+    }
+
+    // Kind of a line that can be known without reading it, when no quote or comment is
+    // open and it has no syntax that can open one.
+    fn plain_kind(&self, line: &[u8]) -> Option<LineKind> {
+        if !self.is_plain() {
+            None
+        } else if trim(line).is_empty() {
+            Some(LineKind::Blank)
+        } else if self
+            .language
+            .important_syntax()
+            .iter()
+            .any(|syntax| contains(line, syntax.as_bytes()))
+        {
+            None
+        } else if self.language.is_literate() || self.starts_with_line_comment(line) {
+            Some(LineKind::Comment)
+        } else {
+            Some(LineKind::Code)
+        }
+    }
+
+    // Reads the line starting at `start`, opening and closing quotes and comments, until an
+    // embedded block is found, given with the offset where the text goes on after it.
+    fn read(
+        &mut self,
+        text: &[u8],
+        start: usize,
+        end: usize,
+        config: &Config,
+        blocks: &Blocks,
+    ) -> Option<(usize, Vec<Event>)> {
+        let mut skip = 0;
+        for i in start..end {
+            if skip != 0 {
+                skip -= 1;
+                continue;
+            }
+            let window = &text[i..];
+            if trim(window).is_empty() {
+                break;
+            }
+            if let Some(length) = self
+                .end_of_quote(window)
+                .or_else(|| self.end_of_comment(window))
+            {
+                skip = length - 1;
+                continue;
+            } else if self.quote.is_some() {
+                continue;
+            }
+            if let Some(block) = self.block(text, i, end, config, blocks) {
+                return Some(block);
+            }
+            if let Some(length) = self.quote(window).or_else(|| self.comment(window)) {
+                skip = length - 1;
+                continue;
+            }
+            if self.is_plain() && self.starts_with_line_comment(window) {
+                break;
+            }
+        }
+        None
+    }
+
+    // Whether a line that isn't plain is a comment: the doc strings when configured, the
+    // lines in a comment and the lines starting one.
+    fn line_is_comment(&self, line: &[u8], config: &Config, started_in_comments: bool) -> bool {
+        if self.quote.is_some() {
+            return self.quote_is_doc_quote && config.treat_doc_strings_as_comments == Some(true);
+        }
+        let trimmed = trim(line);
+        let is_whole_comment = |(start, end): &(&str, &str)| {
+            trimmed.starts_with(start.as_bytes()) && trimmed.ends_with(end.as_bytes())
+        };
+        let starts_open_comment = |last: &&str| {
+            self.comments()
+                .any(|(start, end)| end == last && trimmed.starts_with(start.as_bytes()))
+        };
+        started_in_comments
+            || self.starts_with_line_comment(trimmed)
+            || self.comments().any(is_whole_comment)
+            || self.stack.last().map_or(false, starts_open_comment)
+    }
+
+    // Embedded block starting at `start`, with the lines counted for it and the offset
+    // where the text goes on after it.
+    fn block(
+        &self,
+        text: &[u8],
+        start: usize,
+        end: usize,
+        config: &Config,
+        blocks: &Blocks,
+    ) -> Option<(usize, Vec<Event>)> {
+        if !self.is_plain() {
+            return None;
+        }
+        match self.language {
+            LanguageType::Markdown | LanguageType::UnrealDeveloperMarkdown => {
+                if !contains(&text[start..end], b"```") {
+                    return None;
+                }
+                let (opening_fence, closing_fence) = &blocks.fences;
+                let opening = opening_fence.find(&text[start..end])?;
+                let start_of_code = start + opening.end();
+                let closing = closing_fence.find(&text[start_of_code..]);
+                let end_of_code = closing.map_or(text.len(), |fence| start_of_code + fence.start());
+                let end_of_block = closing.map_or(text.len(), |fence| start_of_code + fence.end());
+                let language = trim(opening.as_bytes())[3..]
+                    .split(|b| *b == b',')
+                    .find_map(|name| LanguageType::from_str(&String::from_utf8_lossy(name)).ok())?;
+
+                // The fences are comments of the text, the code is trimmed as a whole.
+                let code = &text[start_of_code..end_of_code];
+                let range = trim_range(code);
+                let mut events = vec![(start, LineKind::Comment, None)];
+                events.extend(embedded(
+                    language,
+                    &code[range.clone()],
+                    config,
+                    blocks,
+                    |at| start_of_code + range.start + at,
+                ));
+                if closing.is_some() {
+                    events.push((end_of_code, LineKind::Comment, None));
+                }
+                Some((end_of_block, events))
+            }
+            LanguageType::Rust => {
+                let rest = trim_start(&text[start..]);
+                let syntax: &[u8] = match () {
+                    _ if rest.starts_with(b"///") => b"///",
+                    _ if rest.starts_with(b"//!") => b"//!",
+                    _ => return None,
+                };
+
+                // The doc comments are read as markdown without the comment syntax, keeping
+                // where each line starts in both texts.
+                let mut markdown = Vec::new();
+                let mut lines = Vec::new();
+                let mut end_of_block = text.len();
+                let mut line_start = start;
+                while line_start < text.len() {
+                    let end = line_end(text, line_start);
+                    if !trim(&text[line_start..]).starts_with(syntax) {
+                        end_of_block = line_start;
+                        break;
+                    }
+                    let line = trim_start(&text[line_start..end]);
+                    let stripped = &line[3.min(line.len())..];
+                    lines.push((markdown.len(), end - stripped.len()));
+                    markdown.extend_from_slice(stripped);
+                    end_of_block = end;
+                    line_start = end;
+                }
+                let range = trim_range(&markdown);
+                let offset = |at: usize| {
+                    let at = range.start + at;
+                    let line = lines.partition_point(|(start, _)| *start <= at).max(1) - 1;
+                    let (markdown_start, start) = lines[line];
+                    start + at - markdown_start
+                };
+                let events = embedded(
+                    LanguageType::Markdown,
+                    &markdown[range.clone()],
+                    config,
+                    blocks,
+                    offset,
+                );
+                Some((end_of_block, events.collect()))
+            }
+            LanguageType::Html
+            | LanguageType::RubyHtml
+            | LanguageType::Svelte
+            | LanguageType::Vue => {
+                let line = &text[start..end];
+                let (captures, closing, language, default) =
+                    blocks
+                        .html
+                        .iter()
+                        .find_map(|(opening, closing, language, default)| {
+                            let captures = opening.captures(line)?;
+                            Some((captures, closing, language, default))
+                        })?;
+                let start_of_code = start + captures.get(0)?.end();
+                let end_of_code = start_of_code + closing.find(&text[start_of_code..])?.start();
+                let language = captures
+                    .get(1)
+                    .and_then(|name| language(&String::from_utf8_lossy(trim(name.as_bytes()))))
+                    .unwrap_or(*default);
+                let code = &text[start_of_code..end_of_code];
+                if trim(code).is_empty() {
+                    return None;
+                }
+
+                // The opening tag is code of the text, the closing one is read after the block.
+                let range = block_range(code);
+                let mut events = vec![(start, LineKind::Code, None)];
+                events.extend(embedded(
+                    language,
+                    &code[range.clone()],
+                    config,
+                    blocks,
+                    |at| start_of_code + range.start + at,
+                ));
+                Some((end_of_code, events))
+            }
+            _ => None,
+        }
+    }
+
+    fn quote(&mut self, window: &[u8]) -> Option<usize> {
+        if !self.stack.is_empty() {
+            return None;
+        }
+        let language = self.language;
+        let quotes = [
+            (language.doc_quotes(), true, false),
+            (language.verbatim_quotes(), false, true),
+            (language.quotes(), false, false),
+        ];
+        for (quotes, is_doc_quote, is_verbatim) in quotes {
+            if let Some((start, end)) = quotes
+                .iter()
+                .find(|(s, _)| window.starts_with(s.as_bytes()))
+            {
+                self.quote = Some(end);
+                self.quote_is_doc_quote = is_doc_quote;
+                self.quote_is_verbatim = is_verbatim;
+                return Some(start.len());
+            }
+        }
+        None
+    }
+
+    fn end_of_quote(&mut self, window: &[u8]) -> Option<usize> {
+        match self.quote {
+            Some(quote) if window.starts_with(quote.as_bytes()) => {
+                self.quote = None;
+                Some(quote.len())
+            }
+            // An escaped quote is skipped, unless the string is verbatim.
+            _ if !self.quote_is_verbatim
+                && window.starts_with(b"\\")
+                && self
+                    .language
+                    .quotes()
+                    .iter()
+                    .any(|(start, _)| window[1..].starts_with(start.as_bytes())) =>
+            {
+                Some(2)
+            }
+            _ => None,
+        }
+    }
+
+    fn comment(&mut self, window: &[u8]) -> Option<usize> {
+        if self.quote.is_some() {
+            return None;
+        }
+        let language = self.language;
+        let (start, end) = self
+            .comments()
+            .find(|(s, _)| window.starts_with(s.as_bytes()))?;
+        if self.stack.is_empty()
+            || language.allows_nested()
+            || language.nested_comments().contains(&(start, end))
+        {
+            self.stack.push(end);
+        }
+        Some(start.len())
+    }
+
+    fn end_of_comment(&mut self, window: &[u8]) -> Option<usize> {
+        let last = self.stack.last()?;
+        match window.starts_with(last.as_bytes()) {
+            true => self.stack.pop().map(str::len),
+            false => None,
+        }
+    }
+}
+
+// Lines of the text in the order they are counted, the first one counted in each line
+// being its kind.
+fn events(language: LanguageType, text: &[u8], config: &Config, blocks: &Blocks) -> Vec<Event> {
+    let mut events = Vec::new();
+    // The notebooks are json, their cells aren't read line by line.
+    if language == LanguageType::Jupyter {
+        return events;
+    }
+    let mut syntax = Syntax::new(language);
+    let mut start = 0;
+    while start < text.len() {
+        let end = line_end(text, start);
+        // Fortran comments have to start at the first column.
+        let line = match syntax.is_fortran() {
+            true => &text[start..end],
+            false => trim(&text[start..end]),
+        };
+        if let Some(kind) = syntax.plain_kind(line) {
+            events.push((start, kind, None));
+            start = end;
+            continue;
+        }
+
+        let started_in_comments = !syntax.stack.is_empty()
+            || (config.treat_doc_strings_as_comments == Some(true)
+                && syntax.quote.is_some()
+                && syntax.quote_is_doc_quote);
+        if let Some((next, block)) = syntax.read(text, start, end, config, blocks) {
+            events.extend(block);
+            start = next;
+            continue;
+        }
+        let kind = match language.is_literate()
+            || syntax.line_is_comment(line, config, started_in_comments)
+        {
+            true => LineKind::Comment,
+            false => LineKind::Code,
+        };
+        events.push((start, kind, None));
+        start = end;
+    }
+    events
+}
+
+// Kind of each line of the text, None if tokei doesn't count it.
+pub fn classify(language: LanguageType, text: &[u8], config: &Config) -> Vec<Option<LineKind>> {
+    classify_languages(language, text, config)
+        .into_iter()
+        .map(|(kind, _)| kind)
+        .collect()
+}
+
+// Same as `classify`, with the embedded language counting each line, if any. tokei skips
+// some lines, like the blank lines around a markdown code block or the code before a doc
+// comment found at the end of a line.
+pub fn classify_languages(
+    language: LanguageType,
+    text: &[u8],
+    config: &Config,
+) -> Vec<(Option<LineKind>, Option<LanguageType>)> {
+    let ends = line_ends(text);
+    let mut kinds = vec![(None, None); ends.len()];
+    for (at, kind, language) in events(language, text, config, &Blocks::new()) {
+        if let Some(line @ (None, _)) = kinds.get_mut(ends.partition_point(|end| *end <= at)) {
+            *line = (Some(kind), language);
+        }
+    }
+    kinds
+}
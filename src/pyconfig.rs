@@ -1,9 +1,87 @@
+use std::path::PathBuf;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
+use regex::Regex;
 use tokei::Config;
 
+use crate::cache::Cache;
+
+use crate::pysort::PySort;
+use crate::tags::Tagger;
+use crate::walk::{glob_matcher, ignore_files_matcher, FileLimits, Selection};
+
 #[pyclass(name = "Config")]
 pub struct PyConfig {
     pub config: Config,
+    // Options specific to pytokei, not available in tokei's Config.
+    pub cache_dir: Option<PathBuf>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_regex: Option<Regex>,
+    pub exclude_regex: Option<Regex>,
+    pub ignore_files: Vec<PathBuf>,
+    pub custom_ignore_filenames: Vec<String>,
+    pub follow_links: bool,
+    pub max_depth: Option<usize>,
+    pub same_file_system: bool,
+    pub max_file_size: Option<u64>,
+    pub skip_binary: bool,
+    pub skip_minified: bool,
+    pub test_patterns: Vec<String>,
+}
+
+fn check_patterns(name: &str, patterns: Option<Vec<String>>) -> PyResult<Vec<String>> {
+    let patterns = patterns.unwrap_or_default();
+    glob_matcher(&patterns)
+        .map_err(|e| PyValueError::new_err(format!("Invalid {} pattern: {}", name, e)))?;
+    Ok(patterns)
+}
+
+fn check_regex(name: &str, regex: Option<String>) -> PyResult<Option<Regex>> {
+    regex
+        .map(|regex| Regex::new(&regex))
+        .transpose()
+        .map_err(|e| PyValueError::new_err(format!("Invalid {}: {}", name, e)))
+}
+
+impl Default for PyConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// tokei's Config doesn't implement Clone, it's needed to move it to other threads.
+impl Clone for PyConfig {
+    fn clone(&self) -> Self {
+        PyConfig {
+            config: Config {
+                columns: self.config.columns,
+                hidden: self.config.hidden,
+                no_ignore: self.config.no_ignore,
+                no_ignore_parent: self.config.no_ignore_parent,
+                no_ignore_dot: self.config.no_ignore_dot,
+                no_ignore_vcs: self.config.no_ignore_vcs,
+                treat_doc_strings_as_comments: self.config.treat_doc_strings_as_comments,
+                sort: self.config.sort,
+                types: self.config.types.clone(),
+            },
+            cache_dir: self.cache_dir.clone(),
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            include_regex: self.include_regex.clone(),
+            exclude_regex: self.exclude_regex.clone(),
+            ignore_files: self.ignore_files.clone(),
+            custom_ignore_filenames: self.custom_ignore_filenames.clone(),
+            follow_links: self.follow_links,
+            max_depth: self.max_depth,
+            same_file_system: self.same_file_system,
+            max_file_size: self.max_file_size,
+            skip_binary: self.skip_binary,
+            skip_minified: self.skip_minified,
+            test_patterns: self.test_patterns.clone(),
+        }
+    }
 }
 
 #[pymethods]
@@ -12,6 +90,20 @@ impl PyConfig {
     pub fn new() -> Self {
         PyConfig {
             config: Config::default(),
+            cache_dir: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            include_regex: None,
+            exclude_regex: None,
+            ignore_files: Vec::new(),
+            custom_ignore_filenames: Vec::new(),
+            follow_links: false,
+            max_depth: None,
+            same_file_system: false,
+            max_file_size: None,
+            skip_binary: false,
+            skip_minified: false,
+            test_patterns: Vec::new(),
         }
     }
 
@@ -20,6 +112,11 @@ impl PyConfig {
         self.config.columns
     }
 
+    #[setter]
+    pub fn set_columns(&mut self, columns: Option<usize>) {
+        self.config.columns = columns;
+    }
+
     #[getter]
     pub fn hidden(&self) -> Option<bool> {
         self.config.hidden
@@ -50,18 +147,219 @@ impl PyConfig {
         self.config.treat_doc_strings_as_comments
     }
 
+    #[getter]
+    pub fn sort(&self) -> Option<PySort> {
+        self.config.sort.map(|sort| PySort { sort })
+    }
+
+    #[setter]
+    pub fn set_sort(&mut self, sort: Option<PySort>) {
+        self.config.sort = sort.map(|s| s.sort);
+    }
+
+    #[getter]
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.cache_dir.clone()
+    }
+
+    #[setter]
+    pub fn set_cache_dir(&mut self, cache_dir: Option<PathBuf>) {
+        self.cache_dir = cache_dir;
+    }
+
+    #[getter]
+    pub fn include(&self) -> Vec<String> {
+        self.include.clone()
+    }
+
+    #[setter]
+    pub fn set_include(&mut self, include: Option<Vec<String>>) -> PyResult<()> {
+        self.include = check_patterns("include", include)?;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn exclude(&self) -> Vec<String> {
+        self.exclude.clone()
+    }
+
+    #[setter]
+    pub fn set_exclude(&mut self, exclude: Option<Vec<String>>) -> PyResult<()> {
+        self.exclude = check_patterns("exclude", exclude)?;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn include_regex(&self) -> Option<&str> {
+        self.include_regex.as_ref().map(Regex::as_str)
+    }
+
+    #[setter]
+    pub fn set_include_regex(&mut self, include_regex: Option<String>) -> PyResult<()> {
+        self.include_regex = check_regex("include_regex", include_regex)?;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn exclude_regex(&self) -> Option<&str> {
+        self.exclude_regex.as_ref().map(Regex::as_str)
+    }
+
+    #[setter]
+    pub fn set_exclude_regex(&mut self, exclude_regex: Option<String>) -> PyResult<()> {
+        self.exclude_regex = check_regex("exclude_regex", exclude_regex)?;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn ignore_files(&self) -> Vec<PathBuf> {
+        self.ignore_files.clone()
+    }
+
+    #[setter]
+    pub fn set_ignore_files(&mut self, ignore_files: Option<Vec<PathBuf>>) -> PyResult<()> {
+        let ignore_files = ignore_files.unwrap_or_default();
+        ignore_files_matcher(&ignore_files).map_err(|e| match e.is_io() {
+            true => PyIOError::new_err(format!("Error reading the ignore file: {}", e)),
+            false => PyValueError::new_err(format!("Invalid ignore file: {}", e)),
+        })?;
+        self.ignore_files = ignore_files;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn custom_ignore_filenames(&self) -> Vec<String> {
+        self.custom_ignore_filenames.clone()
+    }
+
+    #[setter]
+    pub fn set_custom_ignore_filenames(&mut self, filenames: Option<Vec<String>>) -> PyResult<()> {
+        let filenames = filenames.unwrap_or_default();
+        // They are looked for in every directory, so they can't be paths.
+        if let Some(name) = filenames
+            .iter()
+            .find(|name| name.is_empty() || name.contains(['/', std::path::MAIN_SEPARATOR]))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid ignore file name {:?}, it must be a file name without directories",
+                name
+            )));
+        }
+        self.custom_ignore_filenames = filenames;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn follow_links(&self) -> bool {
+        self.follow_links
+    }
+
+    #[setter]
+    pub fn set_follow_links(&mut self, follow_links: bool) {
+        self.follow_links = follow_links;
+    }
+
+    #[getter]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    #[setter]
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    #[getter]
+    pub fn same_file_system(&self) -> bool {
+        self.same_file_system
+    }
+
+    #[setter]
+    pub fn set_same_file_system(&mut self, same_file_system: bool) {
+        self.same_file_system = same_file_system;
+    }
+
+    #[getter]
+    pub fn max_file_size(&self) -> Option<u64> {
+        self.max_file_size
+    }
+
+    #[setter]
+    pub fn set_max_file_size(&mut self, max_file_size: Option<u64>) {
+        self.max_file_size = max_file_size;
+    }
+
+    #[getter]
+    pub fn skip_binary(&self) -> bool {
+        self.skip_binary
+    }
+
+    #[setter]
+    pub fn set_skip_binary(&mut self, skip_binary: bool) {
+        self.skip_binary = skip_binary;
+    }
+
+    #[getter]
+    pub fn skip_minified(&self) -> bool {
+        self.skip_minified
+    }
+
+    #[setter]
+    pub fn set_skip_minified(&mut self, skip_minified: bool) {
+        self.skip_minified = skip_minified;
+    }
+
+    #[getter]
+    pub fn test_patterns(&self) -> Vec<String> {
+        self.test_patterns.clone()
+    }
+
+    #[setter]
+    pub fn set_test_patterns(&mut self, test_patterns: Option<Vec<String>>) -> PyResult<()> {
+        self.test_patterns = check_patterns("test", test_patterns)?;
+        Ok(())
+    }
+
     #[staticmethod]
     pub fn from_config_files() -> Self {
         PyConfig {
             config: Config::from_config_files(),
+            ..PyConfig::new()
         }
     }
 
     pub fn __repr__(&self) -> &str {
-        return "Config()";
+        "Config()"
     }
 
     // Yet to be defined.
-    //    pub fn sort() -> Option<PySort> {}
     //    pub fn types() -> Option<Vec<PyLanguageType>> {}
 }
+
+impl PyConfig {
+    pub fn cache(&self) -> PyResult<Option<Cache>> {
+        match &self.cache_dir {
+            Some(dir) => Cache::new(dir, &self.config).map(Some).map_err(|e| {
+                PyIOError::new_err(format!("Error creating the cache directory: {}", e))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    pub fn tagger(&self) -> PyResult<Tagger> {
+        Tagger::new(&self.test_patterns)
+            .map_err(|e| PyValueError::new_err(format!("Invalid test pattern: {}", e)))
+    }
+
+    pub fn selection(&self) -> PyResult<Selection> {
+        Selection::new(self).map_err(|e| PyValueError::new_err(format!("Invalid arguments: {}", e)))
+    }
+
+    pub fn limits(&self) -> FileLimits {
+        FileLimits {
+            max_file_size: self.max_file_size,
+            skip_binary: self.skip_binary,
+            skip_minified: self.skip_minified,
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+
+use tokei::{CodeStats, Language, LanguageType, Languages, Report};
+
+use crate::pylanguage_type::PyLanguageType;
+
+type StatsDeltaPlain = HashMap<&'static str, i64>;
+
+// Difference of the counts between two scans, can be negative.
+#[derive(Clone, Default, PartialEq)]
+struct Delta {
+    blanks: i64,
+    code: i64,
+    comments: i64,
+}
+
+impl Delta {
+    fn between(before: &CodeStats, after: &CodeStats) -> Self {
+        Delta {
+            blanks: after.blanks as i64 - before.blanks as i64,
+            code: after.code as i64 - before.code as i64,
+            comments: after.comments as i64 - before.comments as i64,
+        }
+    }
+
+    fn lines(&self) -> i64 {
+        self.blanks + self.code + self.comments
+    }
+
+    fn plain(&self) -> StatsDeltaPlain {
+        HashMap::from([
+            ("blanks", self.blanks),
+            ("code", self.code),
+            ("comments", self.comments),
+            ("lines", self.lines()),
+        ])
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "FileDiff")]
+pub struct PyFileDiff {
+    name: PathBuf,
+    status: &'static str,
+    delta: Delta,
+}
+
+#[pymethods]
+impl PyFileDiff {
+    #[getter]
+    pub fn name(&self) -> PathBuf {
+        self.name.clone()
+    }
+
+    #[getter]
+    pub fn status(&self) -> &str {
+        self.status
+    }
+
+    #[getter]
+    pub fn blanks(&self) -> i64 {
+        self.delta.blanks
+    }
+
+    #[getter]
+    pub fn code(&self) -> i64 {
+        self.delta.code
+    }
+
+    #[getter]
+    pub fn comments(&self) -> i64 {
+        self.delta.comments
+    }
+
+    pub fn lines(&self) -> i64 {
+        self.delta.lines()
+    }
+
+    pub fn plain(&self) -> StatsDeltaPlain {
+        self.delta.plain()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "FileDiff({:?}, {}, code: {:+}, comments: {:+}, blanks: {:+})",
+            self.name, self.status, self.delta.code, self.delta.comments, self.delta.blanks
+        )
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "LanguageDiff")]
+pub struct PyLanguageDiff {
+    delta: Delta,
+    files: i64,
+    added: Vec<PyFileDiff>,
+    removed: Vec<PyFileDiff>,
+    changed: Vec<PyFileDiff>,
+}
+
+#[pymethods]
+impl PyLanguageDiff {
+    #[getter]
+    pub fn blanks(&self) -> i64 {
+        self.delta.blanks
+    }
+
+    #[getter]
+    pub fn code(&self) -> i64 {
+        self.delta.code
+    }
+
+    #[getter]
+    pub fn comments(&self) -> i64 {
+        self.delta.comments
+    }
+
+    #[getter]
+    pub fn added(&self) -> Vec<PyFileDiff> {
+        self.added.clone()
+    }
+
+    #[getter]
+    pub fn removed(&self) -> Vec<PyFileDiff> {
+        self.removed.clone()
+    }
+
+    #[getter]
+    pub fn changed(&self) -> Vec<PyFileDiff> {
+        self.changed.clone()
+    }
+
+    pub fn lines(&self) -> i64 {
+        self.delta.lines()
+    }
+
+    pub fn files(&self) -> i64 {
+        self.files
+    }
+
+    pub fn plain(&self, py: Python) -> HashMap<&'static str, PyObject> {
+        // Obtain the inner content as a dict in Python, ready to be dumped to json.
+        let files_plain = |diffs: &Vec<PyFileDiff>| -> HashMap<String, StatsDeltaPlain> {
+            diffs
+                .iter()
+                .map(|d| (d.name.to_string_lossy().to_string(), d.plain()))
+                .collect()
+        };
+        let mut map: HashMap<&'static str, PyObject> = self
+            .delta
+            .plain()
+            .into_iter()
+            .map(|(k, v)| (k, v.into_py(py)))
+            .collect();
+        map.insert("files", self.files.into_py(py));
+        map.insert("added", files_plain(&self.added).into_py(py));
+        map.insert("removed", files_plain(&self.removed).into_py(py));
+        map.insert("changed", files_plain(&self.changed).into_py(py));
+        map
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "LanguageDiff(files: {:+}, code: {:+}, comments: {:+}, blanks: {:+})",
+            self.files, self.delta.code, self.delta.comments, self.delta.blanks
+        )
+    }
+}
+
+fn language_diff(before: Option<&Language>, after: Option<&Language>) -> PyLanguageDiff {
+    let empty = Language::new();
+    let before = before.unwrap_or(&empty);
+    let after = after.unwrap_or(&empty);
+
+    let reports_before: BTreeMap<&PathBuf, &Report> =
+        before.reports.iter().map(|r| (&r.name, r)).collect();
+    let reports_after: BTreeMap<&PathBuf, &Report> =
+        after.reports.iter().map(|r| (&r.name, r)).collect();
+    let names: BTreeSet<&PathBuf> = reports_before
+        .keys()
+        .chain(reports_after.keys())
+        .cloned()
+        .collect();
+
+    // The lines of the embedded languages count too, like the code blocks of markdown.
+    let summary =
+        |report: Option<&&Report>| report.map_or_else(CodeStats::new, |r| r.stats.summarise());
+    let mut diff = PyLanguageDiff {
+        delta: Delta::default(),
+        files: after.reports.len() as i64 - before.reports.len() as i64,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for name in names {
+        let (old, new) = (reports_before.get(name), reports_after.get(name));
+        let file_diff = |status| PyFileDiff {
+            name: name.clone(),
+            status,
+            delta: Delta::between(&summary(old), &summary(new)),
+        };
+        match (old, new) {
+            (None, Some(_)) => diff.added.push(file_diff("added")),
+            (Some(_), None) => diff.removed.push(file_diff("removed")),
+            (Some(_), Some(_)) => {
+                let file_diff = file_diff("changed");
+                if file_diff.delta != Delta::default() {
+                    diff.changed.push(file_diff);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    let totals = |lang: &Language| {
+        let summary = lang.summarise();
+        let mut stats = CodeStats::new();
+        stats.blanks = summary.blanks;
+        stats.code = summary.code;
+        stats.comments = summary.comments;
+        stats
+    };
+    diff.delta = Delta::between(&totals(before), &totals(after));
+    diff
+}
+
+// Changes per language going from `before` to `after`, languages without changes are omitted.
+pub fn diff_languages(
+    before: &Languages,
+    after: &Languages,
+) -> BTreeMap<LanguageType, PyLanguageDiff> {
+    let types: BTreeSet<&LanguageType> = before.keys().chain(after.keys()).collect();
+    types
+        .into_iter()
+        .map(|ltype| (*ltype, language_diff(before.get(ltype), after.get(ltype))))
+        .filter(|(_, diff)| {
+            diff.files != 0
+                || diff.delta != Delta::default()
+                || !(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty())
+        })
+        .collect()
+}
+
+pub fn diff_map(
+    diffs: BTreeMap<LanguageType, PyLanguageDiff>,
+) -> HashMap<PyLanguageType, PyLanguageDiff> {
+    diffs
+        .into_iter()
+        .map(|(ltype, diff)| (PyLanguageType(ltype), diff))
+        .collect()
+}
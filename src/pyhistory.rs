@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use tokei::Config;
+
+use crate::cache::Cache;
+use crate::git::{Repository, RevisionReader};
+use crate::pyconfig::PyConfig;
+use crate::pylanguages::{git_error, PyLanguages};
+use crate::walk::Selection;
+
+#[pyclass(name = "History")]
+pub struct PyHistory {
+    reader: RevisionReader,
+    commits: VecDeque<(String, i64)>,
+    config: Config,
+    selection: Selection,
+    cache: Option<Cache>,
+}
+
+#[pymethods]
+impl PyHistory {
+    pub fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    pub fn __next__(&mut self, py: Python) -> PyResult<Option<(String, i64, PyLanguages)>> {
+        let (commit, timestamp) = match self.commits.pop_front() {
+            Some(commit) => commit,
+            None => return Ok(None),
+        };
+
+        let PyHistory {
+            reader,
+            config,
+            selection,
+            cache,
+            ..
+        } = self;
+        let reports = py
+            .allow_threads(|| reader.reports(&commit, config, selection, cache.as_ref()))
+            .map_err(git_error)?;
+
+        let mut languages = PyLanguages::new();
+        languages.add_reports(reports, config);
+        Ok(Some((commit, timestamp, languages)))
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.commits.len()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("History(commits: {})", self.commits.len())
+    }
+}
+
+#[pyfunction(revs = "None", every_n = "1", config = "None")]
+pub fn history(
+    py: Python,
+    repo_path: String,
+    revs: Option<Vec<String>>,
+    every_n: usize,
+    config: Option<&PyConfig>,
+) -> PyResult<PyHistory> {
+    if every_n == 0 {
+        return Err(PyValueError::new_err("every_n must be greater than 0"));
+    }
+    let revs = revs.unwrap_or_else(|| vec!["HEAD".to_string()]);
+    let config = config.cloned().unwrap_or_default();
+
+    let (reader, commits) = py
+        .allow_threads(|| {
+            let repo = Repository::open(Path::new(&repo_path))?;
+            let commits = repo.commits(&revs)?;
+            Ok((RevisionReader::new(repo)?, commits))
+        })
+        .map_err(git_error)?;
+
+    // Counted from the last commit, so the latest state is always included.
+    let last = commits.len().saturating_sub(1);
+    let commits = commits
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| (last - i) % every_n == 0)
+        .map(|(_, commit)| commit)
+        .collect();
+
+    Ok(PyHistory {
+        reader,
+        commits,
+        cache: config.cache()?,
+        selection: config.selection()?,
+        config: config.config,
+    })
+}
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_channel::{bounded, Receiver};
+use ignore::WalkState;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use tokei::{LanguageType, Report};
+
+use crate::pyconfig::PyConfig;
+use crate::pylanguage_type::PyLanguageType;
+use crate::pystats::PyReport;
+use crate::tags::Tags;
+use crate::walk::{entry_language, parse_file, walk_builder};
+
+// Maximum number of reports parsed but not yet consumed from python.
+const CHANNEL_CAPACITY: usize = 256;
+
+#[pyclass(name = "ReportsIterator")]
+pub struct PyReportsIterator {
+    receiver: Receiver<(LanguageType, Report, Tags)>,
+    inaccurate: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl PyReportsIterator {
+    pub fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    pub fn __next__(&self, py: Python) -> Option<(PyLanguageType, PyReport)> {
+        // The GIL is released while waiting so the walker threads can keep working.
+        let receiver = self.receiver.clone();
+        py.allow_threads(move || receiver.recv().ok())
+            .map(|(lang_type, report, tags)| (PyLanguageType(lang_type), PyReport { report, tags }))
+    }
+
+    // Set when a file couldn't be read, as `Language.inaccurate` in `get_statistics`.
+    #[getter]
+    pub fn inaccurate(&self) -> bool {
+        self.inaccurate.load(Ordering::Relaxed)
+    }
+
+    pub fn __repr__(&self) -> &str {
+        "ReportsIterator()"
+    }
+}
+
+#[pyfunction]
+pub fn iter_reports(
+    paths: Vec<String>,
+    ignored: Vec<String>,
+    config: &PyConfig,
+) -> PyResult<PyReportsIterator> {
+    let ignored_: Vec<&str> = ignored.iter().map(String::as_str).collect();
+    let walker = walk_builder(&paths, &ignored_, config)
+        .map_err(|e| PyValueError::new_err(format!("Invalid arguments: {}", e)))?;
+
+    let cache = Arc::new(config.cache()?);
+    let limits = config.limits();
+    let tagger = Arc::new(config.tagger()?);
+    let config = Arc::new(config.clone().config);
+    let (tx, rx) = bounded(CHANNEL_CAPACITY);
+    let inaccurate = Arc::new(AtomicBool::new(false));
+    let failed = Arc::clone(&inaccurate);
+
+    thread::spawn(move || {
+        walker.build_parallel().run(move || {
+            let tx = tx.clone();
+            let config = Arc::clone(&config);
+            let cache = Arc::clone(&cache);
+            let tagger = Arc::clone(&tagger);
+            let failed = Arc::clone(&failed);
+            Box::new(move |entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => return WalkState::Continue,
+                };
+                if let Some(language) = entry_language(&entry, &config) {
+                    let depth = entry.depth();
+                    let path = entry.into_path();
+                    let cache = cache.as_ref().as_ref();
+                    let result = parse_file(language, path, None, &config, cache, &limits);
+                    match result {
+                        Ok(Ok(report)) => {
+                            let tags = tagger.tags(&report.name, depth, language);
+                            // The iterator was dropped, there is no one left to read the reports.
+                            if tx.send((language, report, tags)).is_err() {
+                                return WalkState::Quit;
+                            }
+                        }
+                        Ok(Err(_)) => {}
+                        Err(_) => failed.store(true, Ordering::Relaxed),
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+    });
+
+    Ok(PyReportsIterator {
+        receiver: rx,
+        inaccurate,
+    })
+}
@@ -1,19 +1,92 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
-use tokei::Language;
+use tokei::{Language, Report};
 
 use crate::pylanguage_type::PyLanguageType;
 use crate::pysort::PySort;
-use crate::pystats::PyReport;
+use crate::pystats::{richcmp_eq, PyReport};
+use crate::tags::Tags;
 
 type LanguageChildrenPlain = HashMap<String, Vec<HashMap<String, HashMap<&'static str, usize>>>>;
 pub type ReportsPlain = Vec<HashMap<String, HashMap<&'static str, usize>>>;
 
+#[derive(Clone)]
 #[pyclass(name = "Language")]
 pub struct PyLanguage {
     pub language: Language,
+    // Tags of the files of the reports, only the ones with any tag set.
+    pub tags: HashMap<PathBuf, Tags>,
+}
+
+#[derive(FromPyObject)]
+pub enum LanguageOperand<'a> {
+    Language(PyRef<'a, PyLanguage>),
+    Report(PyReport),
+}
+
+// Same as the AddAssign of tokei, but the tokei version replaces the children
+// of the languages found in both instead of extending them.
+fn add_language(language: &mut Language, other: &Language) {
+    language.blanks += other.blanks;
+    language.code += other.code;
+    language.comments += other.comments;
+    language.reports.extend(other.reports.iter().cloned());
+    for (ltype, reports) in &other.children {
+        language
+            .children
+            .entry(*ltype)
+            .or_default()
+            .extend(reports.iter().cloned());
+    }
+    language.inaccurate |= other.inaccurate;
+}
+
+fn add_operand(language: &mut PyLanguage, other: LanguageOperand) {
+    match other {
+        LanguageOperand::Language(other) => {
+            add_language(&mut language.language, &other.language);
+            language.add_tags(&other.tags);
+        }
+        LanguageOperand::Report(other) => {
+            // The totals are updated as total() would do, without the lines of the blobs.
+            language.language.blanks += other.report.stats.blanks;
+            language.language.code += other.report.stats.code;
+            language.language.comments += other.report.stats.comments;
+            language.add_report(other);
+        }
+    }
+}
+
+// Subtracts the counts and removes the reports of the files found in other.
+fn sub_language(language: &Language, other: &Language) -> Option<Language> {
+    let mut result = language.clone();
+    result.blanks = language.blanks.checked_sub(other.blanks)?;
+    result.code = language.code.checked_sub(other.code)?;
+    result.comments = language.comments.checked_sub(other.comments)?;
+
+    let names: HashSet<&PathBuf> = other
+        .reports
+        .iter()
+        .chain(other.children.values().flatten())
+        .map(|r| &r.name)
+        .collect();
+    result.reports.retain(|r| !names.contains(&r.name));
+    for reports in result.children.values_mut() {
+        reports.retain(|r| !names.contains(&r.name));
+    }
+    result.children.retain(|_, reports| !reports.is_empty());
+    Some(result)
+}
+
+impl Default for PyLanguage {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[pymethods]
@@ -24,6 +97,7 @@ impl PyLanguage {
     pub fn new() -> Self {
         PyLanguage {
             language: Language::new(),
+            tags: HashMap::new(),
         }
     }
 
@@ -47,7 +121,7 @@ impl PyLanguage {
         let mut reports = Vec::new();
         let inner = self.language.reports.clone();
         for r in &inner {
-            reports.push(PyReport { report: r.clone() });
+            reports.push(self.py_report(r));
         }
         reports
     }
@@ -68,9 +142,9 @@ impl PyLanguage {
         for (lang_type, reports) in children_.iter() {
             let mut pyreports = Vec::new();
             for r in reports.iter() {
-                pyreports.push(PyReport { report: r.clone() });
+                pyreports.push(self.py_report(r));
             }
-            children.insert(PyLanguageType(lang_type.clone()), pyreports);
+            children.insert(PyLanguageType(*lang_type), pyreports);
         }
         children
     }
@@ -98,12 +172,16 @@ impl PyLanguage {
     }
 
     pub fn add_report(&mut self, report: PyReport) {
+        if report.tags != Tags::default() {
+            self.tags.insert(report.report.name.clone(), report.tags);
+        }
         self.language.add_report(report.report);
     }
 
     pub fn summarise(&self) -> PyLanguage {
         PyLanguage {
             language: self.language.summarise(),
+            tags: self.tags.clone(),
         }
     }
 
@@ -119,6 +197,40 @@ impl PyLanguage {
         self.language.sort_by(category.sort);
     }
 
+    pub fn __add__(&self, other: LanguageOperand) -> PyLanguage {
+        let mut language = self.clone();
+        add_operand(&mut language, other);
+        language
+    }
+
+    pub fn __iadd__(&mut self, other: LanguageOperand) {
+        add_operand(self, other);
+    }
+
+    pub fn __radd__(&self, py: Python, other: usize) -> PyObject {
+        // Allows using sum(), which starts adding the objects to 0.
+        match other {
+            0 => self.clone().into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    pub fn __sub__(&self, other: PyRef<PyLanguage>) -> PyResult<PyLanguage> {
+        match sub_language(&self.language, &other.language) {
+            Some(language) => Ok(PyLanguage {
+                language,
+                tags: self.tags.clone(),
+            }),
+            None => Err(PyValueError::new_err(
+                "The result of the subtraction can't have negative counts",
+            )),
+        }
+    }
+
+    pub fn __richcmp__(&self, py: Python, other: PyRef<PyLanguage>, op: CompareOp) -> PyObject {
+        richcmp_eq(py, &self.language, &other.language, op)
+    }
+
     pub fn __repr__(&self) -> String {
         format!("Language(empty: {:?})", self.is_empty())
     }
@@ -127,3 +239,33 @@ impl PyLanguage {
         self.language.reports.len()
     }
 }
+
+impl PyLanguage {
+    pub fn with_tags(language: Language, tags: &HashMap<PathBuf, Tags>) -> Self {
+        let names: HashSet<&PathBuf> = language
+            .reports
+            .iter()
+            .chain(language.children.values().flatten())
+            .map(|r| &r.name)
+            .collect();
+        let tags = tags
+            .iter()
+            .filter(|(name, _)| names.contains(name))
+            .map(|(name, tags)| (name.clone(), *tags))
+            .collect();
+        PyLanguage { language, tags }
+    }
+
+    fn add_tags(&mut self, tags: &HashMap<PathBuf, Tags>) {
+        for (name, tags) in tags {
+            self.tags.entry(name.clone()).or_insert(*tags);
+        }
+    }
+
+    fn py_report(&self, report: &Report) -> PyReport {
+        PyReport {
+            report: report.clone(),
+            tags: self.tags.get(&report.name).copied().unwrap_or_default(),
+        }
+    }
+}
@@ -9,7 +9,9 @@ use pyo3::prelude::*;
 
 use tokei::LanguageType;
 
-// use crate::pyconfig::PyConfig;
+use crate::lines::{classify_languages, LineKind};
+use crate::pyconfig::PyConfig;
+use crate::pystats::PyCodeStats;
 
 fn language_type_mapper(lang_type: &str) -> Result<LanguageType, pyo3::PyErr> {
     match lang_type {
@@ -296,13 +298,11 @@ impl PyLanguageType {
     }
 
     pub fn line_comments(&self) -> Vec<&str> {
-        let vec = Vec::from(self.0.line_comments());
-        vec
+        Vec::from(self.0.line_comments())
     }
 
     pub fn multi_line_comments(&self) -> Vec<(&str, &str)> {
-        let vec = Vec::from(self.0.multi_line_comments());
-        vec
+        Vec::from(self.0.multi_line_comments())
     }
 
     pub fn allows_nested(&self) -> bool {
@@ -310,33 +310,46 @@ impl PyLanguageType {
     }
 
     pub fn nested_comments(&self) -> Vec<(&str, &str)> {
-        let vec = Vec::from(self.0.nested_comments());
-        vec
+        Vec::from(self.0.nested_comments())
     }
 
     pub fn quotes(&self) -> Vec<(&str, &str)> {
-        let vec = Vec::from(self.0.quotes());
-        vec
+        Vec::from(self.0.quotes())
     }
 
     pub fn verbatim_quotes(&self) -> Vec<(&str, &str)> {
-        let vec = Vec::from(self.0.verbatim_quotes());
-        vec
+        Vec::from(self.0.verbatim_quotes())
     }
 
     pub fn doc_quotes(&self) -> Vec<(&str, &str)> {
-        let vec = Vec::from(self.0.doc_quotes());
-        vec
+        Vec::from(self.0.doc_quotes())
     }
 
     pub fn shebangs(&self) -> Vec<&str> {
-        let vec = Vec::from(self.0.shebangs());
-        vec
+        Vec::from(self.0.shebangs())
     }
 
     pub fn important_syntax(&self) -> Vec<&str> {
-        let vec = Vec::from(self.0.important_syntax());
-        vec
+        Vec::from(self.0.important_syntax())
+    }
+
+    // Kind of each line of the text, with the embedded language it belongs to, if any.
+    pub fn classify_lines(
+        &self,
+        text: &str,
+        config: &PyConfig,
+    ) -> Vec<(Option<&'static str>, Option<PyLanguageType>)> {
+        classify_languages(self.0, text.as_bytes(), &config.config)
+            .into_iter()
+            .map(|(kind, language)| (kind.map(LineKind::name), language.map(PyLanguageType)))
+            .collect()
+    }
+
+    // Statistics of a text of this language, as tokei counts a file.
+    pub fn parse_from_str(&self, text: &str, config: &PyConfig) -> PyCodeStats {
+        PyCodeStats {
+            stats: self.0.parse_from_str(text, &config.config),
+        }
     }
 
     /* TO BE DEVELOPED YET
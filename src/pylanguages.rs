@@ -1,17 +1,54 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::time::{Duration, Instant};
 
+use pyo3::exceptions::PyIOError;
+#[cfg(not(target_os = "linux"))]
+use pyo3::exceptions::PyNotImplementedError;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
-use tokei::Languages;
+use tokei::{CodeStats, Config, Language, LanguageType, Languages, Report, Sort};
 
+use crate::archive::archive_reports;
+use crate::codeowners::{CodeOwners, UNOWNED};
+use crate::git::{Repository, RevisionReader};
 use crate::pyconfig::PyConfig;
+use crate::pydiff::{diff_languages, diff_map, PyLanguageDiff};
 use crate::pylanguage::{PyLanguage, ReportsPlain};
 use crate::pylanguage_type::PyLanguageType;
+use crate::pysort::PySort;
+use crate::pystats::{add_embedded, PyCodeStats, PyReport};
+use crate::render;
+use crate::table::{Printer, FALLBACK_ROW_LEN};
+use crate::tags::Tags;
+use crate::walk::{
+    absolute_path, parse_files, walk_builder, FileState, ParsedFile, ReadFile, SkipReason,
+};
+#[cfg(target_os = "linux")]
+use crate::watch::Inotify;
 
 #[pyclass(name = "Languages")]
 pub struct PyLanguages {
     pub languages: Languages,
+    // Options of the Config used to obtain the statistics, only relevant to print the table.
+    columns: Option<usize>,
+    sort: Option<Sort>,
+    // State of every file parsed, used to find the files to parse again in `update`.
+    files: HashMap<PathBuf, FileState>,
+    // Files found but not counted, they are checked again in every update.
+    skipped: BTreeMap<PathBuf, SkipReason>,
+    // Tags of the files counted, only the ones with any tag set.
+    tags: HashMap<PathBuf, Tags>,
+}
+
+impl Default for PyLanguages {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[pymethods]
@@ -20,31 +57,205 @@ impl PyLanguages {
     pub fn new() -> Self {
         PyLanguages {
             languages: Languages::new(),
+            columns: None,
+            sort: None,
+            files: HashMap::new(),
+            skipped: BTreeMap::new(),
+            tags: HashMap::new(),
         }
     }
 
-    pub fn get_statistics(&mut self, paths: Vec<String>, ignored: Vec<String>, config: &PyConfig) {
-        let paths_: Vec<&str> = paths.iter().map(String::as_str).collect();
-        let paths_ = paths_.as_slice();
-
+    pub fn get_statistics(
+        &mut self,
+        paths: Vec<String>,
+        ignored: Vec<String>,
+        config: &PyConfig,
+    ) -> PyResult<()> {
         let ignored_: Vec<&str> = ignored.iter().map(String::as_str).collect();
-        let ignored_ = ignored_.as_slice();
+        let walker = walk_builder(&paths, &ignored_, config)
+            .map_err(|e| PyValueError::new_err(format!("Invalid arguments: {}", e)))?;
 
-        self.languages
-            .get_statistics(&paths_, &ignored_, &config.config)
+        let cache = config.cache()?;
+        let limits = config.limits();
+        let tagger = config.tagger()?;
+
+        for file in parse_files(
+            walker,
+            &config.config,
+            cache.as_ref(),
+            &limits,
+            &tagger,
+            &HashMap::new(),
+        ) {
+            self.add_parsed(file);
+        }
+        for (_, language) in &mut self.languages {
+            language.total();
+        }
+
+        self.columns = config.config.columns;
+        self.sort = config.config.sort;
+        Ok(())
     }
 
-    pub fn total(&self) -> PyLanguage {
-        PyLanguage {
-            language: self.languages.total(),
+    pub fn get_statistics_archive(
+        &mut self,
+        py: Python,
+        path: String,
+        config: &PyConfig,
+    ) -> PyResult<()> {
+        // Counts the files of a tar, tar.gz or zip archive without extracting it.
+        let cache = config.cache()?;
+        let selection = config.selection()?;
+        let config_ = &config.config;
+
+        let (reports, failed) = py
+            .allow_threads(|| {
+                archive_reports(Path::new(&path), config_, &selection, cache.as_ref())
+            })
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                    PyValueError::new_err(format!("Invalid archive {}: {}", path, e))
+                }
+                _ => PyIOError::new_err(format!("Error reading the archive {}: {}", path, e)),
+            })?;
+        // The files that can't be decoded mark their language as get_statistics does.
+        for language in failed {
+            self.languages
+                .entry(language)
+                .or_default()
+                .mark_inaccurate();
         }
+        self.add_reports(reports, config_);
+        Ok(())
+    }
+
+    pub fn get_statistics_at_revision(
+        &mut self,
+        py: Python,
+        repo_path: String,
+        rev: String,
+        config: &PyConfig,
+    ) -> PyResult<()> {
+        // Counts the files of a revision reading them from the git object database.
+        let cache = config.cache()?;
+        let selection = config.selection()?;
+        let config_ = &config.config;
+
+        let reports = py
+            .allow_threads(|| {
+                let repo = Repository::open(Path::new(&repo_path))?;
+                let commit = repo.resolve(&rev)?;
+                RevisionReader::new(repo)?.reports(&commit, config_, &selection, cache.as_ref())
+            })
+            .map_err(git_error)?;
+        self.add_reports(reports, config_);
+        Ok(())
+    }
+
+    pub fn get_statistics_by_author(
+        &mut self,
+        py: Python,
+        repo_path: String,
+        rev: String,
+        config: &PyConfig,
+    ) -> PyResult<HashMap<String, HashMap<PyLanguageType, PyCodeStats>>> {
+        // Counts the files of a revision and attributes every line to the author
+        // that last changed it according to git blame.
+        let cache = config.cache()?;
+        let selection = config.selection()?;
+        let config_ = &config.config;
+
+        let stats = py
+            .allow_threads(|| {
+                let repo = Repository::open(Path::new(&repo_path))?;
+                let commit = repo.resolve(&rev)?;
+                RevisionReader::new(repo)?.authors(&commit, config_, &selection, cache.as_ref())
+            })
+            .map_err(git_error)?;
+        self.add_reports(stats.reports, config_);
+
+        Ok(py_groups(stats.authors))
+    }
+
+    pub fn get_statistics_for_diff(
+        &mut self,
+        py: Python,
+        repo_path: String,
+        base: String,
+        head: String,
+        config: &PyConfig,
+    ) -> PyResult<HashMap<PyLanguageType, HashMap<&'static str, PyCodeStats>>> {
+        // Counts only the files changed between two revisions, as they are in head,
+        // and returns the lines added and removed per language.
+        let cache = config.cache()?;
+        let selection = config.selection()?;
+        let config_ = &config.config;
+
+        let diff = py
+            .allow_threads(|| {
+                let repo = Repository::open(Path::new(&repo_path))?;
+                let base = repo.resolve(&base)?;
+                let head = repo.resolve(&head)?;
+                RevisionReader::new(repo)?.diff(&base, &head, config_, &selection, cache.as_ref())
+            })
+            .map_err(git_error)?;
+        self.add_reports(diff.reports, config_);
+
+        Ok(diff
+            .languages
+            .into_iter()
+            .map(|(ltype, (added, removed))| {
+                let stats = HashMap::from([
+                    ("added", PyCodeStats { stats: added }),
+                    ("removed", PyCodeStats { stats: removed }),
+                ]);
+                (PyLanguageType(ltype), stats)
+            })
+            .collect())
+    }
+
+    pub fn update(
+        &mut self,
+        paths: Vec<String>,
+        ignored: Vec<String>,
+        config: &PyConfig,
+    ) -> PyResult<HashMap<&'static str, Vec<PathBuf>>> {
+        // Parses again only the files whose modification time or size changed.
+        let changes = self.refresh(&paths, &ignored, config)?;
+        self.columns = config.config.columns;
+        self.sort = config.config.sort;
+        Ok(changes.paths())
+    }
+
+    #[args(ignored = "None", timeout = "None")]
+    pub fn watch(
+        slf: &PyCell<Self>,
+        paths: Vec<String>,
+        config: &PyConfig,
+        callback: PyObject,
+        ignored: Option<Vec<String>>,
+        timeout: Option<f64>,
+    ) -> PyResult<()> {
+        watch_languages(
+            slf,
+            &paths,
+            &ignored.unwrap_or_default(),
+            config,
+            callback,
+            timeout,
+        )
+    }
+
+    pub fn total(&self) -> PyLanguage {
+        PyLanguage::with_tags(self.languages.total(), &self.tags)
     }
 
     pub fn language_names(&self) -> PyResult<Vec<&str>> {
         let vec = self
             .languages
-            .iter()
-            .map(|(lang_type, _)| lang_type.name())
+            .keys()
+            .map(|lang_type| lang_type.name())
             .collect();
         Ok(vec)
     }
@@ -53,9 +264,7 @@ impl PyLanguages {
         let maybe_lang = self.languages.get(&lang_type.0);
 
         match maybe_lang {
-            Some(maybe_lang) => Ok(PyLanguage {
-                language: maybe_lang.clone(),
-            }),
+            Some(maybe_lang) => Ok(PyLanguage::with_tags(maybe_lang.clone(), &self.tags)),
             None => Err(PyValueError::new_err(format!(
                 "LanguageType not found: {}",
                 lang_type.0
@@ -70,10 +279,8 @@ impl PyLanguages {
             .iter()
             .map(|(x, y)| {
                 (
-                    PyLanguageType(x.clone()),
-                    PyLanguage {
-                        language: y.clone(),
-                    },
+                    PyLanguageType(*x),
+                    PyLanguage::with_tags(y.clone(), &self.tags),
                 )
             })
             .collect();
@@ -89,8 +296,158 @@ impl PyLanguages {
         files
     }
 
+    #[args(generated = "None", vendored = "None", test = "None")]
+    pub fn filter(
+        &self,
+        generated: Option<bool>,
+        vendored: Option<bool>,
+        test: Option<bool>,
+    ) -> PyLanguages {
+        self.retain_reports(|tags| {
+            generated.map_or(true, |g| g == tags.generated)
+                && vendored.map_or(true, |v| v == tags.vendored)
+                && test.map_or(true, |t| t == tags.test)
+        })
+    }
+
+    // Totals of the reports with and without the tag, keyed by the name of each part.
+    pub fn split_by(&self, category: &str) -> PyResult<HashMap<&'static str, PyLanguage>> {
+        let (tag, names): (fn(&Tags) -> bool, _) = match category {
+            "test" => (|tags| tags.test, ["test", "production"]),
+            "generated" => (|tags| tags.generated, ["generated", "handwritten"]),
+            "vendored" => (|tags| tags.vendored, ["vendored", "first_party"]),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid category {:?}, expected one of test, generated or vendored",
+                    category
+                )))
+            }
+        };
+        let [tagged, untagged] = names;
+        Ok(HashMap::from([
+            (tagged, self.retain_reports(tag).total()),
+            (untagged, self.retain_reports(|tags| !tag(tags)).total()),
+        ]))
+    }
+
+    #[args(depth = "1", root = "None")]
+    pub fn tree(
+        &self,
+        depth: usize,
+        root: Option<PathBuf>,
+    ) -> HashMap<String, HashMap<PyLanguageType, PyCodeStats>> {
+        // Statistics of every directory up to the given depth, including the files of
+        // its subdirectories, `.` being the root.
+        let names = self
+            .languages
+            .values()
+            .flat_map(|l| l.reports.iter().map(|r| &r.name));
+        let root = root.unwrap_or_else(|| common_dir(names));
+
+        let mut tree: BTreeMap<String, BTreeMap<LanguageType, CodeStats>> = BTreeMap::new();
+        for (ltype, language) in &self.languages {
+            for report in &language.reports {
+                let relative = match report.name.strip_prefix(&root) {
+                    Ok(relative) => relative,
+                    Err(_) => continue,
+                };
+                let dirs: Vec<&str> = relative
+                    .parent()
+                    .into_iter()
+                    .flat_map(Path::components)
+                    .filter_map(|component| match component {
+                        Component::Normal(name) => name.to_str(),
+                        _ => None,
+                    })
+                    .collect();
+                for level in 0..=depth.min(dirs.len()) {
+                    let key = match level {
+                        0 => ".".to_string(),
+                        _ => dirs[..level].join("/"),
+                    };
+                    *tree.entry(key).or_default().entry(*ltype).or_default() +=
+                        report.stats.clone();
+                }
+            }
+        }
+        py_groups(tree)
+    }
+
+    #[args(root = "None")]
+    pub fn group_by_codeowners(
+        &self,
+        path: PathBuf,
+        root: Option<PathBuf>,
+    ) -> PyResult<HashMap<String, HashMap<PyLanguageType, PyCodeStats>>> {
+        // The files with several owners are counted in each of them.
+        let codeowners = CodeOwners::from_file(&path).map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => {
+                PyValueError::new_err(format!("Invalid CODEOWNERS file {}: {}", path.display(), e))
+            }
+            _ => PyIOError::new_err(format!(
+                "Error reading the CODEOWNERS file {}: {}",
+                path.display(),
+                e
+            )),
+        })?;
+        let current_dir = env::current_dir().ok();
+        let root = absolute_path(
+            root.as_deref().unwrap_or(&codeowners.root),
+            current_dir.as_deref(),
+        );
+
+        let mut groups: BTreeMap<String, BTreeMap<LanguageType, CodeStats>> = BTreeMap::new();
+        for (ltype, language) in &self.languages {
+            for report in &language.reports {
+                let name = absolute_path(&report.name, current_dir.as_deref());
+                let mut owners = match name.strip_prefix(&root) {
+                    Ok(relative) => codeowners.owners(relative),
+                    Err(_) => Vec::new(),
+                };
+                if owners.is_empty() {
+                    owners.push(UNOWNED);
+                }
+                for owner in owners {
+                    *groups
+                        .entry(owner.to_string())
+                        .or_default()
+                        .entry(*ltype)
+                        .or_default() += report.stats.clone();
+                }
+            }
+        }
+        Ok(py_groups(groups))
+    }
+
+    // Statistics of the languages embedded in the files of each language, only the
+    // languages with embedded code are included.
+    pub fn embedded_totals(&self) -> HashMap<PyLanguageType, HashMap<PyLanguageType, PyCodeStats>> {
+        self.languages
+            .iter()
+            .filter_map(|(ltype, language)| {
+                let mut embedded = BTreeMap::new();
+                for report in &language.reports {
+                    add_embedded(&report.stats, &mut embedded);
+                }
+                let embedded: HashMap<PyLanguageType, PyCodeStats> = embedded
+                    .into_iter()
+                    .map(|(ltype, stats)| (PyLanguageType(ltype), PyCodeStats { stats }))
+                    .collect();
+                (!embedded.is_empty()).then_some((PyLanguageType(*ltype), embedded))
+            })
+            .collect()
+    }
+
+    // Files not counted because of the limits of the Config, with the reason, by path.
+    pub fn skipped(&self) -> Vec<(PathBuf, &'static str)> {
+        self.skipped
+            .iter()
+            .map(|(path, reason)| (path.clone(), reason.name()))
+            .collect()
+    }
+
     pub fn __repr__(&self) -> &str {
-        return "Languages()";
+        "Languages()"
     }
 
     pub fn get_languages_plain(&self) -> HashMap<&str, ReportsPlain> {
@@ -103,6 +460,7 @@ impl PyLanguages {
                     lang_type.name(),
                     PyLanguage {
                         language: lang.clone(),
+                        tags: HashMap::new(),
                     }
                     .reports_plain(),
                 )
@@ -138,10 +496,498 @@ impl PyLanguages {
                 ("code", summary.code),
                 ("comments", summary.comments),
                 ("blanks", summary.blanks),
-                ("files", lang.reports.len())
+                ("files", lang.reports.len()),
             ]);
             report.insert(ltype.name(), stats);
         }
         report
     }
+
+    pub fn merge(slf: &PyCell<Self>, other: &PyCell<Self>) {
+        // Merging with itself changes nothing, and the instance can't be borrowed twice.
+        if !slf.is(other) {
+            slf.borrow_mut().merge_languages(&other.borrow());
+        }
+    }
+
+    pub fn __add__(&self, other: &PyLanguages) -> PyLanguages {
+        let mut merged = self.copy();
+        merged.merge_languages(other);
+        merged
+    }
+
+    pub fn __iadd__(&mut self, other: &PyLanguages) {
+        self.merge_languages(other)
+    }
+
+    pub fn diff(&self, other: &PyLanguages) -> HashMap<PyLanguageType, PyLanguageDiff> {
+        // Changes going from these statistics to the ones in other.
+        diff_map(diff_languages(&self.languages, &other.languages))
+    }
+
+    pub fn diff_plain(
+        &self,
+        py: Python,
+        other: &PyLanguages,
+    ) -> HashMap<&str, HashMap<&'static str, PyObject>> {
+        // Same as diff, with the language names as keys and the deltas as dicts.
+        diff_languages(&self.languages, &other.languages)
+            .into_iter()
+            .map(|(ltype, diff)| (ltype.name(), diff.plain(py)))
+            .collect()
+    }
+
+    #[args(files = "false", totals = "true", sort = "None")]
+    pub fn to_markdown(&self, files: bool, totals: bool, sort: Option<PySort>) -> String {
+        // Same columns as report_compact_plain, rendered as a markdown table.
+        let sort = sort.map(|s| s.sort).or(self.sort);
+        render::to_markdown(&self.languages, files, totals, sort)
+    }
+
+    #[args(files = "false", totals = "true", sort = "None")]
+    pub fn to_html(&self, files: bool, totals: bool, sort: Option<PySort>) -> String {
+        // Same columns as report_compact_plain, rendered as an html table.
+        let sort = sort.map(|s| s.sort).or(self.sort);
+        render::to_html(&self.languages, files, totals, sort)
+    }
+
+    #[args(metric = "\"code\"", language = "None", style = "\"flat\"")]
+    pub fn badge(
+        &self,
+        metric: &str,
+        language: Option<&PyLanguageType>,
+        style: &str,
+    ) -> PyResult<String> {
+        if !render::BADGE_STYLES.contains(&style) {
+            return Err(PyValueError::new_err(format!(
+                "Badge style doesn't exists: {}, must be one of {:?}",
+                style,
+                render::BADGE_STYLES
+            )));
+        }
+
+        let language = language.map(|l| l.0);
+        if let Some(lang_type) = language {
+            if self.languages.get(&lang_type).is_none() {
+                return Err(PyValueError::new_err(format!(
+                    "LanguageType not found: {}",
+                    lang_type
+                )));
+            }
+        }
+
+        match render::metric_value(&self.languages, metric, language) {
+            Some(value) => {
+                let label = match language {
+                    Some(lang_type) => format!("{} {}", lang_type.name(), metric),
+                    None => metric.to_string(),
+                };
+                Ok(render::badge(&label, value, style))
+            }
+            None => Err(PyValueError::new_err(format!(
+                "Badge metric doesn't exists: {}, must be one of {:?}",
+                metric,
+                render::BADGE_METRICS
+            ))),
+        }
+    }
+
+    #[args(columns = "None", files = "false", compact = "false", sort = "None")]
+    pub fn format_table(
+        &self,
+        columns: Option<usize>,
+        files: bool,
+        compact: bool,
+        sort: Option<PySort>,
+    ) -> PyResult<String> {
+        // Reproduces the table printed by the tokei command line.
+        let columns = columns.or(self.columns).unwrap_or(FALLBACK_ROW_LEN);
+        let sort = sort.map(|s| s.sort).or(self.sort);
+        Printer::new(columns, files)
+            .print(&self.languages, compact, sort)
+            .map_err(|e| PyValueError::new_err(format!("Error formatting the table: {}", e)))
+    }
+}
+
+// Statistics per language of each group of files, like the ones of an author.
+fn py_groups(
+    groups: BTreeMap<String, BTreeMap<LanguageType, CodeStats>>,
+) -> HashMap<String, HashMap<PyLanguageType, PyCodeStats>> {
+    groups
+        .into_iter()
+        .map(|(group, languages)| {
+            let languages = languages
+                .into_iter()
+                .map(|(ltype, stats)| (PyLanguageType(ltype), PyCodeStats { stats }))
+                .collect();
+            (group, languages)
+        })
+        .collect()
+}
+
+// Deepest directory containing all the files.
+fn common_dir<'a>(paths: impl Iterator<Item = &'a PathBuf>) -> PathBuf {
+    let mut common: Option<Vec<Component>> = None;
+    for path in paths {
+        let dir = path.parent().into_iter().flat_map(Path::components);
+        common = Some(match common {
+            None => dir.collect(),
+            Some(common) => common
+                .into_iter()
+                .zip(dir)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    common.unwrap_or_default().into_iter().collect()
+}
+
+pub fn git_error(e: io::Error) -> PyErr {
+    match e.kind() {
+        io::ErrorKind::InvalidInput => {
+            PyValueError::new_err(format!("Invalid git repository or revision: {}", e))
+        }
+        _ => PyIOError::new_err(format!("Error reading the git repository: {}", e)),
+    }
+}
+
+// Reports of the files that changed in a call to `update`.
+#[derive(Default)]
+struct Changes {
+    added: Vec<Report>,
+    modified: Vec<Report>,
+    removed: Vec<Report>,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    fn paths(&self) -> HashMap<&'static str, Vec<PathBuf>> {
+        let names = |reports: &Vec<Report>| reports.iter().map(|r| r.name.clone()).collect();
+        HashMap::from([
+            ("added", names(&self.added)),
+            ("modified", names(&self.modified)),
+            ("removed", names(&self.removed)),
+        ])
+    }
+
+    fn reports(self, tags: &HashMap<PathBuf, Tags>) -> HashMap<&'static str, Vec<PyReport>> {
+        let wrap = |reports: Vec<Report>| {
+            reports
+                .into_iter()
+                .map(|report| PyReport {
+                    tags: tags.get(&report.name).copied().unwrap_or_default(),
+                    report,
+                })
+                .collect()
+        };
+        HashMap::from([
+            ("added", wrap(self.added)),
+            ("modified", wrap(self.modified)),
+            ("removed", wrap(self.removed)),
+        ])
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn watch_languages(
+    slf: &PyCell<PyLanguages>,
+    paths: &[String],
+    ignored: &[String],
+    config: &PyConfig,
+    callback: PyObject,
+    timeout: Option<f64>,
+) -> PyResult<()> {
+    // Time without new events to wait before updating, saving a file usually
+    // produces several of them.
+    const DEBOUNCE: Duration = Duration::from_millis(100);
+    const POLL: Duration = Duration::from_millis(200);
+
+    let py = slf.py();
+    let io_error =
+        |e: std::io::Error| PyIOError::new_err(format!("Error watching the files: {}", e));
+    let inotify = Inotify::new().map_err(io_error)?;
+    let ignored_: Vec<&str> = ignored.iter().map(String::as_str).collect();
+    let watch_dirs = |inotify: &Inotify| -> PyResult<()> {
+        let walker = walk_builder(paths, &ignored_, config)
+            .map_err(|e| PyValueError::new_err(format!("Invalid arguments: {}", e)))?;
+        inotify.add_walker(paths, walker).map_err(io_error)
+    };
+    watch_dirs(&inotify)?;
+
+    let deadline = timeout.map(|t| Instant::now() + Duration::from_secs_f64(t));
+    // The first update picks the changes done before starting to watch.
+    let mut pending = true;
+    loop {
+        py.check_signals()?;
+
+        if pending {
+            pending = false;
+            // The instance is only borrowed while updating so the callback can use it.
+            let changes = slf.borrow_mut().refresh(paths, ignored, config)?;
+            if !changes.is_empty() {
+                let total = slf.borrow().total_plain().into_py(py);
+                let reports = changes.reports(&slf.borrow().tags);
+                let result = callback.call1(py, (reports, total))?;
+                if let Ok(false) = result.extract::<bool>(py) {
+                    return Ok(());
+                }
+            }
+        }
+
+        let wait = match deadline {
+            Some(deadline) if Instant::now() >= deadline => return Ok(()),
+            Some(deadline) => POLL.min(deadline - Instant::now()),
+            None => POLL,
+        };
+        let mut events = py.allow_threads(|| inotify.wait(wait)).map_err(io_error)?;
+        while events.changed {
+            pending = true;
+            if events.new_directories {
+                watch_dirs(&inotify)?;
+            }
+            events = py
+                .allow_threads(|| inotify.wait(DEBOUNCE))
+                .map_err(io_error)?;
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch_languages(
+    _slf: &PyCell<PyLanguages>,
+    _paths: &[String],
+    _ignored: &[String],
+    _config: &PyConfig,
+    _callback: PyObject,
+    _timeout: Option<f64>,
+) -> PyResult<()> {
+    Err(PyNotImplementedError::new_err(
+        "Languages.watch is only available on linux",
+    ))
+}
+
+impl PyLanguages {
+    // Combines the reports of other, the files already present are not added again.
+    fn merge_languages(&mut self, other: &PyLanguages) {
+        let mut known: HashSet<PathBuf> = self
+            .languages
+            .values()
+            .flat_map(|lang| lang.reports.iter().map(|r| r.name.clone()))
+            .collect();
+
+        for (ltype, lang) in &other.languages {
+            let language = self.languages.entry(*ltype).or_default();
+            language.inaccurate |= lang.inaccurate;
+            for report in &lang.reports {
+                if known.insert(report.name.clone()) {
+                    language.add_report(report.clone());
+                }
+            }
+        }
+        self.languages
+            .retain(|_, language| !language.reports.is_empty() || language.inaccurate);
+        for (_, language) in &mut self.languages {
+            language.total();
+        }
+
+        for (path, state) in &other.files {
+            self.files
+                .entry(path.clone())
+                .or_insert_with(|| state.clone());
+        }
+        // A file counted in any of them isn't skipped.
+        for (path, reason) in &other.skipped {
+            self.skipped.entry(path.clone()).or_insert(*reason);
+        }
+        self.skipped.retain(|path, _| !known.contains(path));
+        for (path, tags) in &other.tags {
+            self.tags.entry(path.clone()).or_insert(*tags);
+        }
+        self.columns = self.columns.or(other.columns);
+        self.sort = self.sort.or(other.sort);
+    }
+
+    // Adds the reports of files not found in the disk, like the ones of a git revision.
+    pub fn add_reports(&mut self, files: Vec<ReadFile>, config: &Config) {
+        for file in files {
+            match file.result {
+                Ok(report) => {
+                    if file.tags != Tags::default() {
+                        self.tags.insert(file.name, file.tags);
+                    }
+                    self.languages
+                        .entry(file.language)
+                        .or_default()
+                        .add_report(report);
+                }
+                Err(reason) => {
+                    self.skipped.insert(file.name, reason);
+                }
+            }
+        }
+        for (_, language) in &mut self.languages {
+            language.total();
+        }
+        self.columns = config.columns;
+        self.sort = config.sort;
+    }
+
+    // Languages can't be cloned, so the copy is done language by language.
+    fn copy(&self) -> PyLanguages {
+        let mut languages = Languages::new();
+        for (ltype, lang) in &self.languages {
+            languages.insert(*ltype, lang.clone());
+        }
+        PyLanguages {
+            languages,
+            columns: self.columns,
+            sort: self.sort,
+            files: self.files.clone(),
+            skipped: self.skipped.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    fn refresh(
+        &mut self,
+        paths: &[String],
+        ignored: &[String],
+        config: &PyConfig,
+    ) -> PyResult<Changes> {
+        let ignored_: Vec<&str> = ignored.iter().map(String::as_str).collect();
+        let walker = walk_builder(paths, &ignored_, config)
+            .map_err(|e| PyValueError::new_err(format!("Invalid arguments: {}", e)))?;
+        let cache = config.cache()?;
+        let limits = config.limits();
+        let tagger = config.tagger()?;
+        let (skipped, parsed): (Vec<ParsedFile>, Vec<ParsedFile>) = parse_files(
+            walker,
+            &config.config,
+            cache.as_ref(),
+            &limits,
+            &tagger,
+            &self.files,
+        )
+        .into_iter()
+        .partition(|f| f.skipped.is_some());
+        // The files skipped before and now counted are added, the ones counted before
+        // and now skipped are removed.
+        self.skipped.clear();
+        for file in skipped {
+            self.add_parsed(file);
+        }
+
+        let found: HashSet<&PathBuf> = parsed.iter().map(|f| &f.path).collect();
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| !found.contains(path))
+            .cloned()
+            .collect();
+
+        let mut outdated: HashSet<PathBuf> = removed.iter().cloned().collect();
+        for file in parsed.iter().filter(|f| f.result.is_some()) {
+            if self.files.contains_key(&file.path) {
+                outdated.insert(file.path.clone());
+            }
+        }
+        let mut previous: HashMap<PathBuf, Report> = self
+            .remove_reports(&outdated)
+            .into_iter()
+            .map(|report| (report.name.clone(), report))
+            .collect();
+
+        let mut changes = Changes::default();
+        for path in removed {
+            self.files.remove(&path);
+            self.tags.remove(&path);
+            // Files that couldn't be read don't have a report.
+            let report = previous.remove(&path).unwrap_or_else(|| Report::new(path));
+            changes.removed.push(report);
+        }
+        for file in parsed.into_iter().filter(|f| f.result.is_some()) {
+            let report = match &file.result {
+                Some(Ok(report)) => report.clone(),
+                _ => Report::new(file.path.clone()),
+            };
+            if self.files.contains_key(&file.path) {
+                changes.modified.push(report);
+            } else {
+                changes.added.push(report);
+            }
+            self.add_parsed(file);
+        }
+        for (_, language) in &mut self.languages {
+            language.total();
+        }
+        Ok(changes)
+    }
+
+    fn add_parsed(&mut self, file: ParsedFile) {
+        if let Some(reason) = file.skipped {
+            self.skipped.insert(file.path, reason);
+            return;
+        }
+        if file.result.is_some() {
+            if file.tags == Tags::default() {
+                self.tags.remove(&file.path);
+            } else {
+                self.tags.insert(file.path.clone(), file.tags);
+            }
+        }
+        let language = self.languages.entry(file.state.language).or_default();
+        match file.result {
+            Some(Ok(report)) => language.add_report(report),
+            Some(Err(_)) => language.mark_inaccurate(),
+            None => {}
+        }
+        self.files.insert(file.path, file.state);
+    }
+
+    // Copy with only the reports of the files whose tags satisfy `keep`.
+    fn retain_reports(&self, keep: impl Fn(&Tags) -> bool) -> PyLanguages {
+        let excluded: HashSet<PathBuf> = self
+            .languages
+            .values()
+            .flat_map(|language| language.reports.iter().map(|r| &r.name))
+            .filter(|name| !keep(&self.tags.get(*name).copied().unwrap_or_default()))
+            .cloned()
+            .collect();
+        let mut languages = self.copy();
+        languages.remove_reports(&excluded);
+        for (_, language) in &mut languages.languages {
+            language.total();
+        }
+        languages.files.retain(|path, _| !excluded.contains(path));
+        languages.tags.retain(|path, _| !excluded.contains(path));
+        languages
+    }
+
+    // Removes the reports of the given files, the totals must be computed afterwards.
+    fn remove_reports(&mut self, paths: &HashSet<PathBuf>) -> Vec<Report> {
+        let mut removed = Vec::new();
+        for (_, language) in &mut self.languages {
+            if !language.reports.iter().any(|r| paths.contains(&r.name)) {
+                continue;
+            }
+            // The children are obtained from the reports, so the language is rebuilt.
+            let mut rebuilt = Language::new();
+            rebuilt.inaccurate = language.inaccurate;
+            for report in language.reports.drain(..) {
+                if paths.contains(&report.name) {
+                    removed.push(report);
+                } else {
+                    rebuilt.add_report(report);
+                }
+            }
+            *language = rebuilt;
+        }
+        self.languages
+            .retain(|_, language| !language.reports.is_empty() || language.inaccurate);
+        removed
+    }
 }
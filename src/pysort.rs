@@ -24,6 +24,7 @@ impl PySort {
     }
 
     #[staticmethod]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, pyo3::PyErr> {
         Self::new(s)
     }
@@ -35,12 +36,11 @@ impl PySort {
 
 #[pyfunction]
 pub fn sort_types() -> Vec<String> {
-    let sort_types = Vec::from([
+    Vec::from([
         "Blanks".to_string(),
         "Comments".to_string(),
         "Code".to_string(),
         "Files".to_string(),
         "Lines".to_string(),
-    ]);
-    sort_types
+    ])
 }
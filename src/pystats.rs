@@ -1,9 +1,67 @@
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
-use tokei::{CodeStats, Report};
+use tokei::{CodeStats, LanguageType, Report};
 
+use crate::pylanguage::PyLanguage;
 use crate::pylanguage_type::PyLanguageType;
+use crate::tags::Tags;
+
+// Subtracts the counts and the blobs, None if any of them would be negative.
+fn checked_sub(stats: &CodeStats, other: &CodeStats) -> Option<CodeStats> {
+    let mut result = CodeStats::new();
+    result.blanks = stats.blanks.checked_sub(other.blanks)?;
+    result.code = stats.code.checked_sub(other.code)?;
+    result.comments = stats.comments.checked_sub(other.comments)?;
+
+    for (ltype, blob) in &stats.blobs {
+        let blob = match other.blobs.get(ltype) {
+            Some(other_blob) => checked_sub(blob, other_blob)?,
+            None => blob.clone(),
+        };
+        // Keeps the equality of (a + b) - b and a.
+        if blob != CodeStats::new() {
+            result.blobs.insert(*ltype, blob);
+        }
+    }
+    if other
+        .blobs
+        .iter()
+        .any(|(ltype, blob)| !stats.blobs.contains_key(ltype) && *blob != CodeStats::new())
+    {
+        return None;
+    }
+    Some(result)
+}
+
+pub fn sub_stats(stats: &CodeStats, other: &CodeStats) -> PyResult<CodeStats> {
+    checked_sub(stats, other).ok_or_else(|| {
+        PyValueError::new_err("The result of the subtraction can't have negative counts")
+    })
+}
+
+// Adds the counts of the languages embedded in the stats, like the code blocks of markdown,
+// the blocks nested in them are counted in their own language.
+pub fn add_embedded(stats: &CodeStats, embedded: &mut BTreeMap<LanguageType, CodeStats>) {
+    for (ltype, blob) in &stats.blobs {
+        let counts = embedded.entry(*ltype).or_default();
+        counts.blanks += blob.blanks;
+        counts.code += blob.code;
+        counts.comments += blob.comments;
+        add_embedded(blob, embedded);
+    }
+}
+
+// Only equality is defined, the rest of comparisons aren't supported.
+pub fn richcmp_eq<T: PartialEq>(py: Python, a: &T, b: &T, op: CompareOp) -> PyObject {
+    match op {
+        CompareOp::Eq => (a == b).into_py(py),
+        CompareOp::Ne => (a != b).into_py(py),
+        _ => py.NotImplemented(),
+    }
+}
 
 #[derive(Clone)]
 #[pyclass(name = "CodeStats")]
@@ -11,6 +69,12 @@ pub struct PyCodeStats {
     pub stats: CodeStats,
 }
 
+impl Default for PyCodeStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[pymethods]
 impl PyCodeStats {
     #[new]
@@ -44,7 +108,7 @@ impl PyCodeStats {
             .iter()
             .map(|(ltype, cstats)| {
                 (
-                    PyLanguageType(ltype.clone()),
+                    PyLanguageType(*ltype),
                     PyCodeStats {
                         stats: cstats.clone(),
                     },
@@ -83,18 +147,45 @@ impl PyCodeStats {
         new_stats.stats.blanks = summ.blanks;
         new_stats.stats.code = summ.code;
         new_stats.stats.comments = summ.comments;
-        return new_stats;
+        new_stats
     }
 
     pub fn plain(&self) -> HashMap<&'static str, usize> {
         // Obtain the inner content as a dict in Python.
-        let map = HashMap::from([
+        HashMap::from([
             ("blanks", self.blanks()),
             ("code", self.code()),
             ("comments", self.comments()),
             ("lines", self.lines()),
-        ]);
-        map
+        ])
+    }
+
+    pub fn __add__(&self, other: PyCodeStats) -> PyCodeStats {
+        PyCodeStats {
+            stats: self.stats.clone() + other.stats,
+        }
+    }
+
+    pub fn __iadd__(&mut self, other: PyCodeStats) {
+        self.stats += other.stats;
+    }
+
+    pub fn __radd__(&self, py: Python, other: usize) -> PyObject {
+        // Allows using sum(), which starts adding the objects to 0.
+        match other {
+            0 => self.clone().into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    pub fn __sub__(&self, other: PyCodeStats) -> PyResult<PyCodeStats> {
+        Ok(PyCodeStats {
+            stats: sub_stats(&self.stats, &other.stats)?,
+        })
+    }
+
+    pub fn __richcmp__(&self, py: Python, other: PyCodeStats, op: CompareOp) -> PyObject {
+        richcmp_eq(py, &self.stats, &other.stats, op)
     }
 
     pub fn __repr__(&self) -> PyResult<String> {
@@ -112,6 +203,7 @@ impl PyCodeStats {
 #[pyclass(name = "Report")]
 pub struct PyReport {
     pub report: Report,
+    pub tags: Tags,
 }
 
 #[pymethods]
@@ -121,6 +213,7 @@ impl PyReport {
         let path = PathBuf::from(name);
         PyReport {
             report: Report::new(path),
+            tags: Tags::default(),
         }
     }
 
@@ -131,10 +224,57 @@ impl PyReport {
 
     #[getter]
     pub fn stats(&self) -> PyCodeStats {
-        let stats = PyCodeStats {
+        PyCodeStats {
             stats: self.report.stats.clone(),
-        };
-        stats
+        }
+    }
+
+    #[getter]
+    pub fn is_generated(&self) -> bool {
+        self.tags.generated
+    }
+
+    #[getter]
+    pub fn is_vendored(&self) -> bool {
+        self.tags.vendored
+    }
+
+    #[getter]
+    pub fn is_test(&self) -> bool {
+        self.tags.test
+    }
+
+    pub fn __add__(&self, other: PyCodeStats) -> PyReport {
+        let mut report = self.clone();
+        report.report += other.stats;
+        report
+    }
+
+    pub fn __iadd__(&mut self, other: PyCodeStats) {
+        self.report += other.stats;
+    }
+
+    pub fn __radd__(&self, py: Python, other: usize) -> PyObject {
+        // sum() of reports obtains the Language containing all of them.
+        match other {
+            0 => {
+                let mut language = PyLanguage::new();
+                language.add_report(self.clone());
+                language.total();
+                language.into_py(py)
+            }
+            _ => py.NotImplemented(),
+        }
+    }
+
+    pub fn __sub__(&self, other: PyCodeStats) -> PyResult<PyReport> {
+        let mut report = self.clone();
+        report.report.stats = sub_stats(&report.report.stats, &other.stats)?;
+        Ok(report)
+    }
+
+    pub fn __richcmp__(&self, py: Python, other: PyReport, op: CompareOp) -> PyObject {
+        richcmp_eq(py, &self.report, &other.report, op)
     }
 
     pub fn __repr__(&self) -> PyResult<String> {
@@ -142,10 +282,18 @@ impl PyReport {
     }
 
     pub fn plain(&self) -> HashMap<String, HashMap<&'static str, usize>> {
-        let map = HashMap::from([(
+        HashMap::from([(
             self.name().into_os_string().into_string().unwrap(),
             self.stats().plain(),
-        )]);
-        map
+        )])
+    }
+
+    pub fn embedded(&self) -> HashMap<PyLanguageType, PyCodeStats> {
+        let mut embedded = BTreeMap::new();
+        add_embedded(&self.report.stats, &mut embedded);
+        embedded
+            .into_iter()
+            .map(|(ltype, stats)| (PyLanguageType(ltype), PyCodeStats { stats }))
+            .collect()
     }
 }
@@ -0,0 +1,305 @@
+use std::cmp::Reverse;
+
+use tokei::{CodeStats, Language, LanguageType, Languages, Sort};
+
+const HEADERS: [&str; 6] = ["Language", "Files", "Lines", "Code", "Comments", "Blanks"];
+
+#[derive(PartialEq)]
+enum RowKind {
+    Language,
+    File,
+    Total,
+}
+
+// A single line of the rendered table, shared by every output format.
+struct Row {
+    kind: RowKind,
+    name: String,
+    files: Option<usize>,
+    stats: CodeStats,
+}
+
+impl Row {
+    fn values(&self) -> [String; 5] {
+        [
+            self.files.map(|f| f.to_string()).unwrap_or_default(),
+            self.stats.lines().to_string(),
+            self.stats.code.to_string(),
+            self.stats.comments.to_string(),
+            self.stats.blanks.to_string(),
+        ]
+    }
+}
+
+// Summarised stats of a language, counting the lines of its children.
+fn summary_stats(lang: &Language) -> CodeStats {
+    let summary = lang.summarise();
+    let mut stats = CodeStats::new();
+    stats.blanks = summary.blanks;
+    stats.code = summary.code;
+    stats.comments = summary.comments;
+    stats
+}
+
+// Value of the column used to sort the rows, in descending order like the tokei table.
+fn sort_key(sort: Sort, stats: &CodeStats, files: usize) -> Reverse<usize> {
+    Reverse(match sort {
+        Sort::Blanks => stats.blanks,
+        Sort::Comments => stats.comments,
+        Sort::Code => stats.code,
+        Sort::Files => files,
+        Sort::Lines => stats.lines(),
+    })
+}
+
+// Collects the same columns shown by `report_compact_plain`, optionally
+// followed by the reports of every language and a row with the totals.
+// Without a sort the languages and the files are ordered by name.
+fn rows(languages: &Languages, files: bool, totals: bool, sort: Option<Sort>) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut total = CodeStats::new();
+    let mut total_files = 0;
+
+    let mut sorted: Vec<(&LanguageType, &Language, CodeStats)> = languages
+        .iter()
+        .map(|(ltype, lang)| (ltype, lang, summary_stats(lang)))
+        .collect();
+    if let Some(sort) = sort {
+        sorted.sort_by_key(|(_, lang, stats)| sort_key(sort, stats, lang.reports.len()));
+    }
+
+    for (ltype, lang, stats) in sorted {
+        total += stats.clone();
+        total_files += lang.reports.len();
+
+        rows.push(Row {
+            kind: RowKind::Language,
+            name: ltype.name().to_string(),
+            files: Some(lang.reports.len()),
+            stats,
+        });
+
+        if files {
+            let mut reports: Vec<_> = lang
+                .reports
+                .iter()
+                .map(|report| (report, report.stats.summarise()))
+                .collect();
+            reports.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+            if let Some(sort) = sort {
+                reports.sort_by_key(|(_, stats)| sort_key(sort, stats, 1));
+            }
+            for (report, stats) in reports {
+                rows.push(Row {
+                    kind: RowKind::File,
+                    name: report.name.to_string_lossy().to_string(),
+                    files: None,
+                    stats,
+                });
+            }
+        }
+    }
+
+    if totals {
+        rows.push(Row {
+            kind: RowKind::Total,
+            name: "Total".to_string(),
+            files: Some(total_files),
+            stats: total,
+        });
+    }
+    rows
+}
+
+pub fn to_markdown(languages: &Languages, files: bool, totals: bool, sort: Option<Sort>) -> String {
+    let mut out = format!("| {} |\n", HEADERS.join(" | "));
+    out.push_str("|:---|---:|---:|---:|---:|---:|\n");
+
+    for row in rows(languages, files, totals, sort) {
+        let values = row.values();
+        let name = row.name.replace('|', "\\|");
+        let line = match row.kind {
+            RowKind::Language => format!("| {} | {} |", name, values.join(" | ")),
+            RowKind::File => format!("| `{}` | {} |", name, values.join(" | ")),
+            RowKind::Total => {
+                let values: Vec<String> = values.iter().map(|v| format!("**{}**", v)).collect();
+                format!("| **{}** | {} |", name, values.join(" | "))
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_row(row: &Row) -> String {
+    let mut cells = String::new();
+    let name = escape_html(&row.name);
+    match row.kind {
+        RowKind::Total => cells.push_str(&format!("<th>{}</th>", name)),
+        _ => cells.push_str(&format!("<td>{}</td>", name)),
+    }
+    for value in row.values() {
+        cells.push_str(&format!("<td>{}</td>", value));
+    }
+
+    match row.kind {
+        RowKind::Language => format!("    <tr class=\"language\">{}</tr>\n", cells),
+        RowKind::File => format!("    <tr class=\"file\">{}</tr>\n", cells),
+        RowKind::Total => format!("    <tr class=\"total\">{}</tr>\n", cells),
+    }
+}
+
+pub fn to_html(languages: &Languages, files: bool, totals: bool, sort: Option<Sort>) -> String {
+    let rows = rows(languages, files, totals, sort);
+
+    let mut out = String::from("<table>\n  <thead>\n    <tr>");
+    for header in HEADERS {
+        out.push_str(&format!("<th>{}</th>", header));
+    }
+    out.push_str("</tr>\n  </thead>\n  <tbody>\n");
+
+    for row in rows.iter().filter(|r| r.kind != RowKind::Total) {
+        out.push_str(&html_row(row));
+    }
+    out.push_str("  </tbody>\n");
+
+    if let Some(row) = rows.iter().find(|r| r.kind == RowKind::Total) {
+        out.push_str("  <tfoot>\n");
+        out.push_str(&html_row(row));
+        out.push_str("  </tfoot>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+// Approximate width in pixels of a text written in 11px Verdana,
+// the font used by the badges.
+fn text_width(text: &str) -> usize {
+    let width: f64 = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '!' | '|' | '\'' => 3.2,
+            'f' | 'r' | 't' | 'I' | ' ' | '(' | ')' | '[' | ']' | '-' => 4.6,
+            'm' | 'w' | 'M' | 'W' | '%' | '@' => 10.0,
+            c if c.is_ascii_uppercase() => 7.6,
+            _ => 6.8,
+        })
+        .sum();
+    width.ceil() as usize
+}
+
+fn format_count(count: usize) -> String {
+    // Rounded to tenths of the unit before picking it, 999_950 is 1.0M and not 1000.0k.
+    let thousands = (count + 50) / 100;
+    let millions = (count + 50_000) / 100_000;
+    match count {
+        0..=999 => count.to_string(),
+        _ if thousands < 10_000 => format!("{}.{}k", thousands / 10, thousands % 10),
+        _ => format!("{}.{}M", millions / 10, millions % 10),
+    }
+}
+
+pub const BADGE_METRICS: [&str; 5] = ["code", "lines", "comments", "blanks", "files"];
+pub const BADGE_STYLES: [&str; 2] = ["flat", "flat-square"];
+
+// Value of the metric for a single language or, if not given, for every language.
+pub fn metric_value(
+    languages: &Languages,
+    metric: &str,
+    language: Option<LanguageType>,
+) -> Option<usize> {
+    let mut files = 0;
+    let mut stats = CodeStats::new();
+    for (ltype, lang) in languages {
+        if language.map_or(true, |l| l == *ltype) {
+            files += lang.reports.len();
+            stats += summary_stats(lang);
+        }
+    }
+
+    match metric {
+        "code" => Some(stats.code),
+        "lines" => Some(stats.lines()),
+        "comments" => Some(stats.comments),
+        "blanks" => Some(stats.blanks),
+        "files" => Some(files),
+        _ => None,
+    }
+}
+
+// Renders a shields-style badge, the caller is expected to validate the style.
+pub fn badge(label: &str, value: usize, style: &str) -> String {
+    let value = format_count(value);
+    let label = escape_html(label);
+    let label_width = text_width(&label) + 10;
+    let value_width = text_width(&value) + 10;
+    let width = label_width + value_width;
+    // Text coordinates are scaled by 10 to keep the precision of the half pixels.
+    let label_x = label_width * 5;
+    let value_x = label_width * 10 + value_width * 5;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"20\" role=\"img\" aria-label=\"{l}: {v}\">\
+<title>{l}: {v}</title>",
+        w = width,
+        l = label,
+        v = value,
+    );
+
+    let shadow = |x: usize, text: &str| {
+        format!(
+            "<text x=\"{}\" y=\"150\" fill=\"#010101\" fill-opacity=\".3\" transform=\"scale(.1)\">{}</text>",
+            x, text
+        )
+    };
+
+    if style == "flat" {
+        out.push_str(&format!(
+            "<linearGradient id=\"s\" x2=\"0\" y2=\"100%\">\
+<stop offset=\"0\" stop-color=\"#bbb\" stop-opacity=\".1\"/><stop offset=\"1\" stop-opacity=\".1\"/>\
+</linearGradient>\
+<clipPath id=\"r\"><rect width=\"{w}\" height=\"20\" rx=\"3\" fill=\"#fff\"/></clipPath>\
+<g clip-path=\"url(#r)\"><rect width=\"{lw}\" height=\"20\" fill=\"#555\"/>\
+<rect x=\"{lw}\" width=\"{vw}\" height=\"20\" fill=\"#007ec6\"/>\
+<rect width=\"{w}\" height=\"20\" fill=\"url(#s)\"/></g>",
+            w = width,
+            lw = label_width,
+            vw = value_width,
+        ));
+    } else {
+        out.push_str(&format!(
+            "<g shape-rendering=\"crispEdges\"><rect width=\"{lw}\" height=\"20\" fill=\"#555\"/>\
+<rect x=\"{lw}\" width=\"{vw}\" height=\"20\" fill=\"#007ec6\"/></g>",
+            lw = label_width,
+            vw = value_width,
+        ));
+    }
+
+    out.push_str(
+        "<g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" font-size=\"110\">",
+    );
+    if style == "flat" {
+        out.push_str(&shadow(label_x, &label));
+    }
+    out.push_str(&format!(
+        "<text x=\"{}\" y=\"140\" transform=\"scale(.1)\">{}</text>",
+        label_x, label
+    ));
+    if style == "flat" {
+        out.push_str(&shadow(value_x, &value));
+    }
+    out.push_str(&format!(
+        "<text x=\"{}\" y=\"140\" transform=\"scale(.1)\">{}</text>",
+        value_x, value
+    ));
+    out.push_str("</g></svg>\n");
+    out
+}
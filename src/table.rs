@@ -0,0 +1,352 @@
+// Port of the `Printer` used by the tokei binary (`src/cli_utils.rs`), writing
+// to a `String` instead of stdout so the output matches the command line table.
+use std::cmp::Reverse;
+use std::fmt::{self, Write};
+
+use tokei::{CodeStats, Language, LanguageType, Languages, Report, Sort};
+
+pub const FALLBACK_ROW_LEN: usize = 79;
+const NO_LANG_HEADER_ROW_LEN: usize = 67;
+const NO_LANG_ROW_LEN: usize = 61;
+const NO_LANG_ROW_LEN_NO_SPACES: usize = 54;
+const IDENT_INACCURATE: &str = "(!)";
+
+pub struct Printer {
+    out: String,
+    columns: usize,
+    path_length: usize,
+    row: String,
+    subrow: String,
+    list_files: bool,
+}
+
+impl Printer {
+    pub fn new(columns: usize, list_files: bool) -> Self {
+        let columns = columns.max(FALLBACK_ROW_LEN);
+        Printer {
+            out: String::new(),
+            columns,
+            path_length: columns - NO_LANG_ROW_LEN_NO_SPACES,
+            row: "=".repeat(columns),
+            subrow: "-".repeat(columns),
+            list_files,
+        }
+    }
+
+    fn print_header(&mut self) -> fmt::Result {
+        self.print_row()?;
+        writeln!(
+            self.out,
+            " {:<6$} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "Language",
+            "Files",
+            "Lines",
+            "Code",
+            "Comments",
+            "Blanks",
+            self.columns - NO_LANG_HEADER_ROW_LEN
+        )?;
+        self.print_row()
+    }
+
+    fn print_inaccuracy_warning(&mut self) -> fmt::Result {
+        writeln!(
+            self.out,
+            "Note: results can be inaccurate for languages marked with '{}'",
+            IDENT_INACCURATE
+        )
+    }
+
+    fn print_language(&mut self, language: &Language, name: &str) -> fmt::Result {
+        self.print_language_name(language.inaccurate, name, None)?;
+        write!(self.out, " ")?;
+        writeln!(
+            self.out,
+            "{:>6} {:>12} {:>12} {:>12} {:>12}",
+            language.reports.len(),
+            language.lines(),
+            language.code,
+            language.comments,
+            language.blanks,
+        )
+    }
+
+    fn print_language_in_print_total(&mut self, language: &Language) -> fmt::Result {
+        self.print_language_name(language.inaccurate, "Total", None)?;
+        write!(self.out, " ")?;
+        writeln!(
+            self.out,
+            "{:>6} {:>12} {:>12} {:>12} {:>12}",
+            language.children.values().map(Vec::len).sum::<usize>(),
+            language.lines(),
+            language.code,
+            language.comments,
+            language.blanks,
+        )
+    }
+
+    fn print_language_name(
+        &mut self,
+        inaccurate: bool,
+        name: &str,
+        prefix: Option<&str>,
+    ) -> fmt::Result {
+        let mut lang_section_len = self.columns - NO_LANG_ROW_LEN - prefix.map_or(0, str::len);
+        if inaccurate {
+            lang_section_len -= IDENT_INACCURATE.len();
+        }
+
+        if let Some(prefix) = prefix {
+            write!(self.out, "{}", prefix)?;
+        }
+        // truncate and replace the last char with a `|` if the name is too long
+        if lang_section_len < name.len() {
+            write!(self.out, " {:.len$}", name, len = lang_section_len - 1)?;
+            write!(self.out, "|")?;
+        } else {
+            write!(self.out, " {:<len$}", name, len = lang_section_len)?;
+        }
+        if inaccurate {
+            write!(self.out, "{}", IDENT_INACCURATE)?;
+        };
+
+        Ok(())
+    }
+
+    fn print_code_stats(
+        &mut self,
+        language_type: LanguageType,
+        stats: &[CodeStats],
+    ) -> fmt::Result {
+        self.print_language_name(false, &language_type.to_string(), Some(" |-"))?;
+        let mut code = 0;
+        let mut comments = 0;
+        let mut blanks = 0;
+
+        for stats in stats.iter().map(|s| s.summarise()) {
+            code += stats.code;
+            comments += stats.comments;
+            blanks += stats.blanks;
+        }
+
+        if !stats.is_empty() {
+            writeln!(
+                self.out,
+                " {:>6} {:>12} {:>12} {:>12} {:>12}",
+                stats.len(),
+                code + comments + blanks,
+                code,
+                comments,
+                blanks,
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    fn print_language_total(&mut self, parent: &Language) -> fmt::Result {
+        for (language, reports) in &parent.children {
+            self.print_code_stats(
+                *language,
+                &reports
+                    .iter()
+                    .map(|r| r.stats.summarise())
+                    .collect::<Vec<_>>(),
+            )?;
+        }
+        let mut subtotal = Report::new("(Total)".into());
+        let summary = parent.summarise();
+        subtotal.stats.code += summary.code;
+        subtotal.stats.comments += summary.comments;
+        subtotal.stats.blanks += summary.blanks;
+        self.print_report_with_name(&subtotal)?;
+
+        Ok(())
+    }
+
+    fn print_results<'a, I>(&mut self, languages: I, compact: bool) -> fmt::Result
+    where
+        I: Iterator<Item = (&'a LanguageType, &'a Language)>,
+    {
+        let (a, b): (Vec<_>, Vec<_>) = languages
+            .filter(|(_, v)| !v.is_empty())
+            .partition(|(_, l)| compact || l.children.is_empty());
+        let mut first = true;
+
+        for languages in &[&a, &b] {
+            for &(name, language) in *languages {
+                let has_children = !(compact || language.children.is_empty());
+                if first {
+                    first = false;
+                } else if has_children || self.list_files {
+                    self.print_subrow()?;
+                }
+
+                self.print_language(language, name.name())?;
+                if has_children {
+                    self.print_language_total(language)?;
+                }
+
+                if self.list_files {
+                    self.print_subrow()?;
+                    let (a, b): (Vec<_>, Vec<_>) = language
+                        .reports
+                        .iter()
+                        .partition(|r| r.stats.blobs.is_empty());
+                    for reports in &[&a, &b] {
+                        let mut first = true;
+                        for report in reports.iter() {
+                            if !report.stats.blobs.is_empty() {
+                                if first && a.is_empty() {
+                                    writeln!(self.out, " {}", report.name.display())?;
+                                    first = false;
+                                } else {
+                                    writeln!(
+                                        self.out,
+                                        "-- {} {}",
+                                        report.name.display(),
+                                        "-".repeat(
+                                            self.columns
+                                                - 4
+                                                - report.name.display().to_string().len()
+                                        )
+                                    )?;
+                                }
+                                let mut new_report = (*report).clone();
+                                new_report.name = name.to_string().into();
+                                writeln!(self.out, " |-{:1$}", new_report, self.path_length - 3)?;
+                                self.print_report_total(report, language.inaccurate)?;
+                            } else {
+                                writeln!(self.out, "{:1$}", report, self.path_length)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_row(&mut self) -> fmt::Result {
+        writeln!(self.out, "{}", self.row)
+    }
+
+    fn print_subrow(&mut self) -> fmt::Result {
+        writeln!(self.out, "{}", self.subrow)
+    }
+
+    fn print_report(
+        &mut self,
+        language_type: LanguageType,
+        stats: &CodeStats,
+        inaccurate: bool,
+    ) -> fmt::Result {
+        self.print_language_name(inaccurate, &language_type.to_string(), Some(" |-"))?;
+
+        writeln!(
+            self.out,
+            " {:>6} {:>12} {:>12} {:>12} {:>12}",
+            " ",
+            stats.lines(),
+            stats.code,
+            stats.comments,
+            stats.blanks,
+        )
+    }
+
+    fn print_report_total(&mut self, report: &Report, inaccurate: bool) -> fmt::Result {
+        if report.stats.blobs.is_empty() {
+            return Ok(());
+        }
+
+        let mut subtotal = Report::new("|- (Total)".into());
+        subtotal.stats.code += report.stats.code;
+        subtotal.stats.comments += report.stats.comments;
+        subtotal.stats.blanks += report.stats.blanks;
+
+        for (language_type, stats) in &report.stats.blobs {
+            self.print_report(*language_type, stats, inaccurate)?;
+            subtotal.stats += stats.summarise();
+        }
+
+        self.print_report_with_name(report)?;
+
+        Ok(())
+    }
+
+    fn print_report_with_name(&mut self, report: &Report) -> fmt::Result {
+        // tokei computes a truncated name for long paths but prints the full one,
+        // the same is done here to keep the output identical.
+        let name = report.name.to_string_lossy();
+        self.print_report_total_formatted(&name, self.path_length, report)
+    }
+
+    fn print_report_total_formatted(
+        &mut self,
+        name: &str,
+        max_len: usize,
+        report: &Report,
+    ) -> fmt::Result {
+        writeln!(
+            self.out,
+            " {: <max$} {:>12} {:>12} {:>12} {:>12}",
+            name,
+            report.stats.lines(),
+            report.stats.code,
+            report.stats.comments,
+            report.stats.blanks,
+            max = max_len
+        )
+    }
+
+    fn print_total(&mut self, languages: &Languages) -> fmt::Result {
+        let total = languages.total();
+        self.print_row()?;
+        self.print_language_in_print_total(&total)?;
+        self.print_row()
+    }
+
+    // Writes the whole table, in the same order as the `main` function of tokei.
+    pub fn print(
+        mut self,
+        languages: &Languages,
+        compact: bool,
+        sort: Option<Sort>,
+    ) -> Result<String, fmt::Error> {
+        if languages.iter().any(|(_, lang)| lang.inaccurate) {
+            self.print_inaccuracy_warning()?;
+        }
+
+        self.print_header()?;
+
+        if let Some(sort_category) = sort {
+            let sorted: Vec<(&LanguageType, Language)> = languages
+                .iter()
+                .map(|(ltype, lang)| {
+                    let mut lang = lang.clone();
+                    lang.sort_by(sort_category);
+                    (ltype, lang)
+                })
+                .collect();
+            let mut sorted: Vec<(&LanguageType, &Language)> =
+                sorted.iter().map(|(ltype, lang)| (*ltype, lang)).collect();
+
+            match sort_category {
+                Sort::Blanks => sorted.sort_by_key(|l| Reverse(l.1.blanks)),
+                Sort::Comments => sorted.sort_by_key(|l| Reverse(l.1.comments)),
+                Sort::Code => sorted.sort_by_key(|l| Reverse(l.1.code)),
+                Sort::Files => sorted.sort_by_key(|l| Reverse(l.1.reports.len())),
+                Sort::Lines => sorted.sort_by_key(|l| Reverse(l.1.lines())),
+            }
+
+            self.print_results(sorted.into_iter(), compact)?
+        } else {
+            self.print_results(languages.iter(), compact)?
+        }
+
+        self.print_total(languages)?;
+
+        Ok(self.out)
+    }
+}
@@ -0,0 +1,268 @@
+// Classification of the files as generated or vendored code, following the rules of
+// github's linguist: markers in the header, well known directories and the attributes
+// `linguist-generated` and `linguist-vendored` of the `.gitattributes` files. Also as
+// test code, from the paths and the usual names of the test files of each language.
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use tokei::LanguageType;
+
+use crate::walk::{absolute_path, decode, glob_matcher};
+
+const ATTRIBUTES_FILE: &str = ".gitattributes";
+// Directories whose files are considered vendored.
+const VENDORED_DIRS: &[&str] = &[
+    "vendor",
+    "vendors",
+    "node_modules",
+    "bower_components",
+    "third_party",
+    "third-party",
+    "3rdparty",
+];
+// Directories whose files are considered tests, in any language.
+const TEST_DIRS: &[&str] = &[
+    "test/",
+    "tests/",
+    "spec/",
+    "specs/",
+    "__tests__/",
+    "testdata/",
+];
+// Names of the test files of each language.
+const TEST_FILES: &[(LanguageType, &[&str])] = &[
+    (LanguageType::CSharp, &["*Test.cs", "*Tests.cs"]),
+    (
+        LanguageType::Cpp,
+        &["*_test.cc", "*_test.cpp", "*_unittest.cc"],
+    ),
+    (LanguageType::Dart, &["*_test.dart"]),
+    (LanguageType::Elixir, &["*_test.exs"]),
+    (LanguageType::Go, &["*_test.go"]),
+    (LanguageType::Java, &["*Test.java", "*Tests.java"]),
+    (
+        LanguageType::JavaScript,
+        &["*.test.js", "*.spec.js", "*.test.mjs"],
+    ),
+    (LanguageType::Jsx, &["*.test.jsx", "*.spec.jsx"]),
+    (LanguageType::Kotlin, &["*Test.kt", "*Tests.kt"]),
+    (LanguageType::Php, &["*Test.php"]),
+    (
+        LanguageType::Python,
+        &["test_*.py", "*_test.py", "conftest.py"],
+    ),
+    (LanguageType::Ruby, &["*_test.rb", "*_spec.rb"]),
+    (LanguageType::Scala, &["*Test.scala", "*Spec.scala"]),
+    (LanguageType::Swift, &["*Tests.swift"]),
+    (LanguageType::Tsx, &["*.test.tsx", "*.spec.tsx"]),
+    (LanguageType::TypeScript, &["*.test.ts", "*.spec.ts"]),
+];
+// Part of the files where the generated markers are looked for.
+const HEADER_LINES: usize = 10;
+const HEADER_SIZE: u64 = 4096;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tags {
+    pub generated: bool,
+    pub vendored: bool,
+    pub test: bool,
+}
+
+// Like `// Code generated by protoc-gen-go. DO NOT EDIT.` or `@generated`.
+pub fn is_generated_header(text: &[u8]) -> bool {
+    let header: Vec<&[u8]> = text.split(|b| *b == b'\n').take(HEADER_LINES).collect();
+    let header = String::from_utf8_lossy(&header.join(&b'\n')).to_lowercase();
+    header.contains("@generated")
+        || (header.contains("generated") && header.contains("do not edit"))
+}
+
+// Whether any of the directories of the path is a vendored one.
+fn in_vendored_dir(relative: &Path) -> bool {
+    relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .any(|component| match component {
+            Component::Normal(name) => name.to_str().map_or(false, |n| VENDORED_DIRS.contains(&n)),
+            _ => false,
+        })
+}
+
+// State of an attribute in a line of a `.gitattributes` file: None if it isn't
+// mentioned, Some(None) if it's unspecified with `!attribute`.
+type AttributeState = Option<Option<bool>>;
+
+fn attribute_state(attributes: &[&str], name: &str) -> AttributeState {
+    // The last mention of the attribute in the line wins.
+    attributes.iter().rev().find_map(|attribute| {
+        let (state, rest) = match attribute.as_bytes().first() {
+            Some(b'-') => (Some(false), &attribute[1..]),
+            Some(b'!') => (None, &attribute[1..]),
+            _ => (Some(true), *attribute),
+        };
+        match rest.strip_prefix(name)? {
+            "" => Some(state),
+            "=true" if state == Some(true) => Some(Some(true)),
+            "=false" if state == Some(true) => Some(Some(false)),
+            _ => None,
+        }
+    })
+}
+
+struct AttributeRule {
+    matcher: Gitignore,
+    generated: AttributeState,
+    vendored: AttributeState,
+}
+
+// Lines of the `.gitattributes` file of a directory that set the attributes of linguist.
+fn read_rules(dir: &Path) -> Vec<Arc<AttributeRule>> {
+    let content = match fs::read_to_string(dir.join(ATTRIBUTES_FILE)) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next()?;
+            let attributes: Vec<&str> = parts.collect();
+            let generated = attribute_state(&attributes, "linguist-generated");
+            let vendored = attribute_state(&attributes, "linguist-vendored");
+            if generated.is_none() && vendored.is_none() {
+                return None;
+            }
+            let mut builder = GitignoreBuilder::new(dir);
+            builder.add_line(None, pattern).ok()?;
+            Some(Arc::new(AttributeRule {
+                matcher: builder.build().ok()?,
+                generated,
+                vendored,
+            }))
+        })
+        .collect()
+}
+
+type Rules = Arc<Vec<Arc<AttributeRule>>>;
+
+// Tags the files found by the walker, the attributes of each directory are read once.
+pub struct Tagger {
+    current_dir: Option<PathBuf>,
+    rules: Mutex<HashMap<PathBuf, Rules>>,
+    // Test patterns of the languages with specific ones, and of the rest.
+    test_files: HashMap<LanguageType, Gitignore>,
+    test_dirs: Gitignore,
+}
+
+impl Tagger {
+    // The test patterns given are added to the default ones.
+    pub fn new(test_patterns: &[String]) -> Result<Self, ignore::Error> {
+        let matcher = |files: &[&str]| {
+            let patterns: Vec<String> = TEST_DIRS
+                .iter()
+                .chain(files)
+                .map(|pattern| pattern.to_string())
+                .chain(test_patterns.iter().cloned())
+                .collect();
+            glob_matcher(&patterns)
+        };
+        Ok(Tagger {
+            current_dir: env::current_dir().ok(),
+            rules: Mutex::new(HashMap::new()),
+            test_files: TEST_FILES
+                .iter()
+                .map(|(language, files)| Ok((*language, matcher(files)?)))
+                .collect::<Result<_, ignore::Error>>()?,
+            test_dirs: matcher(&[])?,
+        })
+    }
+
+    // Rules applied to the files of a directory, from the outermost to the innermost.
+    fn rules(&self, dir: &Path) -> Rules {
+        let cached = self.rules.lock().unwrap().get(dir).cloned();
+        if let Some(rules) = cached {
+            return rules;
+        }
+        // The attributes of the directories above the repository don't apply.
+        let mut rules = match dir.parent() {
+            Some(parent) if !dir.join(".git").exists() => self.rules(parent).as_ref().clone(),
+            _ => Vec::new(),
+        };
+        rules.extend(read_rules(dir));
+        let rules = Arc::new(rules);
+        self.rules
+            .lock()
+            .unwrap()
+            .insert(dir.to_path_buf(), Arc::clone(&rules));
+        rules
+    }
+
+    // Whether a file is a test one, from its path relative to the path walked.
+    fn is_test(&self, relative: &Path, language: LanguageType) -> bool {
+        self.test_files
+            .get(&language)
+            .unwrap_or(&self.test_dirs)
+            .matched_path_or_any_parents(relative, false)
+            .is_ignore()
+    }
+
+    // Tags of a file that isn't in the disk, from its path relative to the repository path
+    // or the root of the archive, and whether its content has a generated header.
+    pub fn path_tags(&self, relative: &Path, generated: bool, language: LanguageType) -> Tags {
+        Tags {
+            generated,
+            vendored: in_vendored_dir(relative),
+            test: self.is_test(relative, language),
+        }
+    }
+
+    // Tags of a file at the given depth of the walk, the attributes take precedence
+    // over the markers in the header and the directories.
+    pub fn tags(&self, path: &Path, depth: usize, language: LanguageType) -> Tags {
+        // Only the part below the path walked is used, to ignore the directories containing
+        // the project, the name of the file if it's the path walked.
+        let components: Vec<Component> = path.components().collect();
+        let start = components.len().saturating_sub(depth.max(1));
+        let relative: PathBuf = components[start..]
+            .iter()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+
+        let absolute = absolute_path(path, self.current_dir.as_deref());
+        let (mut generated, mut vendored) = (None, None);
+        if let Some(dir) = absolute.parent() {
+            for rule in self.rules(dir).iter().rev() {
+                if generated.is_some() && vendored.is_some() {
+                    break;
+                }
+                if rule.matcher.matched(&absolute, false).is_ignore() {
+                    generated = generated.or(rule.generated);
+                    vendored = vendored.or(rule.vendored);
+                }
+            }
+        }
+
+        Tags {
+            generated: generated.flatten().unwrap_or_else(|| {
+                let mut header = Vec::new();
+                File::open(path)
+                    .and_then(|file| file.take(HEADER_SIZE).read_to_end(&mut header))
+                    .and_then(|_| decode(&header))
+                    .map_or(false, |text| is_generated_header(&text))
+            }),
+            vendored: vendored
+                .flatten()
+                .unwrap_or_else(|| in_vendored_dir(&relative)),
+            test: self.is_test(&relative, language),
+        }
+    }
+}
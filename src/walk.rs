@@ -0,0 +1,495 @@
+// Directory walking, mirrors `tokei::utils::fs::get_all_files` which isn't public,
+// to be able to process the files as they are found.
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::OverrideBuilder;
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use regex::Regex;
+
+use tokei::{Config, LanguageType, Report};
+
+use crate::cache::Cache;
+use crate::pyconfig::PyConfig;
+use crate::tags::{is_generated_header, Tagger, Tags};
+
+const IGNORE_FILE: &str = ".tokeignore";
+// Average length of the lines above which a file is considered minified.
+pub const MINIFIED_LINE_LENGTH: usize = 200;
+
+// Absolute path without `.` nor `..`, without resolving the links like `canonicalize`,
+// to compare the paths found by the walker with others.
+pub fn absolute_path(path: &Path, current_dir: Option<&Path>) -> PathBuf {
+    let joined = match current_dir {
+        Some(current_dir) if path.is_relative() => current_dir.join(path),
+        _ => path.to_path_buf(),
+    };
+    let mut absolute = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::ParentDir if absolute.file_name().is_some() => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+    absolute
+}
+
+// Matcher of gitignore-style patterns, used with the paths relative to the paths walked.
+pub fn glob_matcher(patterns: &[String]) -> Result<Gitignore, ignore::Error> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        builder.add_line(None, pattern)?;
+    }
+    builder.build()
+}
+
+// Matcher of the patterns of the ignore files, used like `glob_matcher`.
+pub fn ignore_files_matcher(files: &[PathBuf]) -> Result<Gitignore, ignore::Error> {
+    let mut builder = GitignoreBuilder::new("");
+    for file in files {
+        if let Some(error) = builder.add(file) {
+            return Err(error);
+        }
+    }
+    builder.build()
+}
+
+// Include and exclude filters of `PyConfig`, applied to the entries found by the walker
+// and to the files read from git revisions and archives.
+pub struct Filters {
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+    ignore_files: Option<Gitignore>,
+    include_regex: Option<Regex>,
+    exclude_regex: Option<Regex>,
+}
+
+impl Filters {
+    // None when nothing has to be filtered.
+    pub fn new(py_config: &PyConfig) -> Result<Option<Self>, ignore::Error> {
+        let ignore = py_config.config.no_ignore.map(|b| !b).unwrap_or(true);
+        let ignore_files = ignore && !py_config.ignore_files.is_empty();
+        if !ignore_files
+            && py_config.include.is_empty()
+            && py_config.exclude.is_empty()
+            && py_config.include_regex.is_none()
+            && py_config.exclude_regex.is_none()
+        {
+            return Ok(None);
+        }
+        Ok(Some(Filters {
+            include: match py_config.include.is_empty() {
+                true => None,
+                false => Some(glob_matcher(&py_config.include)?),
+            },
+            exclude: glob_matcher(&py_config.exclude)?,
+            ignore_files: match ignore_files {
+                true => Some(ignore_files_matcher(&py_config.ignore_files)?),
+                false => None,
+            },
+            include_regex: py_config.include_regex.clone(),
+            exclude_regex: py_config.exclude_regex.clone(),
+        }))
+    }
+
+    // Whether a path, relative to the path walked, has to be counted.
+    pub fn is_counted(&self, relative: &Path, is_dir: bool) -> bool {
+        let is_excluded = |matcher: &Gitignore| {
+            matcher
+                .matched_path_or_any_parents(relative, is_dir)
+                .is_ignore()
+        };
+        if is_excluded(&self.exclude) || self.ignore_files.as_ref().map_or(false, is_excluded) {
+            return false;
+        }
+        if is_dir {
+            return true;
+        }
+        let text = relative
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, "/");
+        let included = self.include.as_ref().map_or(true, |include| {
+            include
+                .matched_path_or_any_parents(relative, false)
+                .is_ignore()
+        });
+        included
+            && self
+                .include_regex
+                .as_ref()
+                .map_or(true, |r| r.is_match(&text))
+            && !self
+                .exclude_regex
+                .as_ref()
+                .map_or(false, |r| r.is_match(&text))
+    }
+
+    fn is_entry_counted(&self, roots: &[PathBuf], entry: &DirEntry) -> bool {
+        // The paths walked are never filtered, the rest are relative to the deepest one
+        // containing them.
+        match roots
+            .iter()
+            .filter_map(|root| entry.path().strip_prefix(root).ok())
+            .min_by_key(|relative| relative.components().count())
+        {
+            Some(relative) if !relative.as_os_str().is_empty() => {
+                let is_dir = entry.file_type().map_or(false, |ft| ft.is_dir());
+                self.is_counted(relative, is_dir)
+            }
+            _ => true,
+        }
+    }
+}
+
+// Options of `PyConfig` applied to the files read from a git revision or an archive,
+// which aren't found by the walker. The paths are relative to the repository path or
+// to the root of the archive.
+pub struct Selection {
+    filters: Option<Filters>,
+    limits: FileLimits,
+    tagger: Tagger,
+}
+
+impl Selection {
+    pub fn new(py_config: &PyConfig) -> Result<Self, ignore::Error> {
+        Ok(Selection {
+            filters: Filters::new(py_config)?,
+            limits: py_config.limits(),
+            tagger: Tagger::new(&py_config.test_patterns)?,
+        })
+    }
+
+    pub fn is_counted(&self, relative: &Path) -> bool {
+        self.filters
+            .as_ref()
+            .map_or(true, |filters| filters.is_counted(relative, false))
+    }
+
+    // Whether a file has to be skipped according to the limits, from its content as read
+    // and decoded to utf-8.
+    pub fn check(&self, content: &[u8], text: &[u8]) -> Option<SkipReason> {
+        match self.limits.max_file_size {
+            Some(max_file_size) if content.len() as u64 > max_file_size => Some(SkipReason::Size),
+            _ => self.limits.check_text(text),
+        }
+    }
+
+    // Tags of a file from its content and its relative path, the `.gitattributes` files
+    // aren't read.
+    pub fn tags(&self, relative: &Path, text: &[u8], language: LanguageType) -> Tags {
+        self.tagger
+            .path_tags(relative, is_generated_header(text), language)
+    }
+
+    pub fn path_tags(&self, relative: &Path, generated: bool, language: LanguageType) -> Tags {
+        self.tagger.path_tags(relative, generated, language)
+    }
+
+    // Same as `parse_text` with the tags of the file, unless it has to be skipped.
+    pub fn parse(
+        &self,
+        language: LanguageType,
+        name: PathBuf,
+        relative: &Path,
+        content: &[u8],
+        config: &Config,
+        cache: Option<&Cache>,
+    ) -> io::Result<ReadFile> {
+        let text = decode(content)?;
+        let (result, tags) = match self.check(content, &text) {
+            Some(reason) => (Err(reason), Tags::default()),
+            None => (
+                Ok(parse_decoded(language, name.clone(), &text, config, cache)),
+                self.tags(relative, &text, language),
+            ),
+        };
+        Ok(ReadFile {
+            language,
+            name,
+            result,
+            tags,
+        })
+    }
+}
+
+// A file read from a git revision or an archive, with its report or why it was skipped.
+pub struct ReadFile {
+    pub language: LanguageType,
+    pub name: PathBuf,
+    pub result: Result<Report, SkipReason>,
+    pub tags: Tags,
+}
+
+pub fn walk_builder<A: AsRef<Path>>(
+    paths: &[A],
+    ignored: &[&str],
+    py_config: &PyConfig,
+) -> Result<WalkBuilder, ignore::Error> {
+    let config = &py_config.config;
+    let roots: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| path.as_ref().to_path_buf())
+        .collect();
+    let mut paths = paths.iter();
+    let mut walker = match paths.next() {
+        Some(path) => WalkBuilder::new(path),
+        None => {
+            return Err(ignore::Error::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "At least one path must be given",
+            )))
+        }
+    };
+
+    for path in paths {
+        walker.add(path);
+    }
+
+    if !ignored.is_empty() {
+        let mut overrides = OverrideBuilder::new(".");
+
+        for ignored in ignored {
+            overrides.add(&format!("!{}", ignored))?;
+        }
+
+        walker.overrides(overrides.build()?);
+    }
+
+    let ignore = config.no_ignore.map(|b| !b).unwrap_or(true);
+    let ignore_dot = ignore && config.no_ignore_dot.map(|b| !b).unwrap_or(true);
+    let ignore_vcs = ignore && config.no_ignore_vcs.map(|b| !b).unwrap_or(true);
+
+    // Custom ignore files always work even if the `ignore` option is false,
+    // so we only add if that option is not present.
+    if ignore_dot {
+        walker.add_custom_ignore_filename(IGNORE_FILE);
+        for filename in &py_config.custom_ignore_filenames {
+            walker.add_custom_ignore_filename(filename);
+        }
+    }
+
+    if let Some(filters) = Filters::new(py_config)? {
+        walker.filter_entry(move |entry| filters.is_entry_counted(&roots, entry));
+    }
+
+    walker
+        .git_exclude(ignore_vcs)
+        .git_global(ignore_vcs)
+        .git_ignore(ignore_vcs)
+        .hidden(config.hidden.map(|b| !b).unwrap_or(true))
+        .ignore(ignore_dot)
+        .parents(ignore && config.no_ignore_parent.map(|b| !b).unwrap_or(true))
+        .follow_links(py_config.follow_links)
+        .max_depth(py_config.max_depth)
+        .same_file_system(py_config.same_file_system);
+
+    Ok(walker)
+}
+
+// Language of a file found by the walker, if it has to be counted.
+pub fn entry_language(entry: &DirEntry, config: &Config) -> Option<LanguageType> {
+    if !entry.file_type().map_or(false, |ft| ft.is_file()) {
+        return None;
+    }
+    let language = LanguageType::from_path(entry.path(), config)?;
+    match config.types.as_deref() {
+        Some(types) if !types.contains(&language) => None,
+        _ => Some(language),
+    }
+}
+
+// Why a file found by the walker wasn't counted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    Size,
+    Binary,
+    Minified,
+}
+
+impl SkipReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            SkipReason::Size => "max_file_size",
+            SkipReason::Binary => "binary",
+            SkipReason::Minified => "minified",
+        }
+    }
+}
+
+// Options of `PyConfig` to skip the files that would distort the statistics.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileLimits {
+    pub max_file_size: Option<u64>,
+    pub skip_binary: bool,
+    pub skip_minified: bool,
+}
+
+impl FileLimits {
+    fn check_text(&self, text: &[u8]) -> Option<SkipReason> {
+        if self.skip_binary && text.contains(&0) {
+            return Some(SkipReason::Binary);
+        }
+        if self.skip_minified {
+            let lines = text
+                .split(|b| *b == b'\n')
+                .filter(|l| !l.is_empty())
+                .count();
+            if lines > 0 && text.len() / lines > MINIFIED_LINE_LENGTH {
+                return Some(SkipReason::Minified);
+            }
+        }
+        None
+    }
+}
+
+// Same as `LanguageType::parse`, but looking first for the stats in the cache if given,
+// the file isn't parsed if it has to be skipped according to `limits`. The size is read
+// from the metadata of the file when it isn't given.
+pub fn parse_file(
+    language: LanguageType,
+    path: PathBuf,
+    size: Option<u64>,
+    config: &Config,
+    cache: Option<&Cache>,
+    limits: &FileLimits,
+) -> io::Result<Result<Report, SkipReason>> {
+    if let Some(max_file_size) = limits.max_file_size {
+        let size = match size {
+            Some(size) => size,
+            None => fs::metadata(&path)?.len(),
+        };
+        if size > max_file_size {
+            return Ok(Err(SkipReason::Size));
+        }
+    }
+    if cache.is_none() && !limits.skip_binary && !limits.skip_minified {
+        return language.parse(path, config).map(Ok).map_err(|(e, _)| e);
+    }
+    let text = decode(&fs::read(&path)?)?;
+    if let Some(reason) = limits.check_text(&text) {
+        return Ok(Err(reason));
+    }
+    Ok(Ok(parse_decoded(language, path, &text, config, cache)))
+}
+
+// Decodes the content of a file to utf-8 as tokei does, only when it isn't already.
+pub fn decode(content: &[u8]) -> io::Result<Vec<u8>> {
+    let mut text = Vec::new();
+    DecodeReaderBytesBuilder::new()
+        .build(content)
+        .read_to_end(&mut text)?;
+    Ok(text)
+}
+
+// Parses the content of a file that may not be in the disk, decoding it as tokei does.
+pub fn parse_text(
+    language: LanguageType,
+    name: PathBuf,
+    content: &[u8],
+    config: &Config,
+    cache: Option<&Cache>,
+) -> io::Result<Report> {
+    let text = decode(content)?;
+    Ok(parse_decoded(language, name, &text, config, cache))
+}
+
+fn parse_decoded(
+    language: LanguageType,
+    name: PathBuf,
+    text: &[u8],
+    config: &Config,
+    cache: Option<&Cache>,
+) -> Report {
+    let mut report = Report::new(name);
+    report += match cache {
+        Some(cache) => cache.parse(language, text, config),
+        None => language.parse_from_slice(text, config),
+    };
+    report
+}
+
+// What is known of a file the last time it was parsed, to detect changes.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FileState {
+    pub language: LanguageType,
+    pub modified: Option<SystemTime>,
+    pub size: u64,
+}
+
+pub struct ParsedFile {
+    pub path: PathBuf,
+    pub state: FileState,
+    // None when the file didn't change with respect to the previous state or was skipped.
+    pub result: Option<Result<Report, io::Error>>,
+    pub skipped: Option<SkipReason>,
+    pub tags: Tags,
+}
+
+// Walks and parses in parallel every file that is not found unchanged in `previous`.
+pub fn parse_files(
+    walker: WalkBuilder,
+    config: &Config,
+    cache: Option<&Cache>,
+    limits: &FileLimits,
+    tagger: &Tagger,
+    previous: &HashMap<PathBuf, FileState>,
+) -> Vec<ParsedFile> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    walker.build_parallel().run(|| {
+        let tx = tx.clone();
+        Box::new(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => return WalkState::Continue,
+            };
+            let language = match entry_language(&entry, config) {
+                Some(language) => language,
+                None => return WalkState::Continue,
+            };
+
+            let metadata = entry.metadata().ok();
+            let size = metadata.as_ref().map(|m| m.len());
+            let state = FileState {
+                language,
+                modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+                size: size.unwrap_or(0),
+            };
+            let depth = entry.depth();
+            let path = entry.into_path();
+
+            let (result, skipped) = if previous.get(&path) == Some(&state) {
+                (None, None)
+            } else {
+                match parse_file(language, path.clone(), size, config, cache, limits) {
+                    Ok(Ok(report)) => (Some(Ok(report)), None),
+                    Ok(Err(reason)) => (None, Some(reason)),
+                    Err(e) => (Some(Err(e)), None),
+                }
+            };
+            let tags = match result {
+                Some(Ok(_)) => tagger.tags(&path, depth, language),
+                _ => Tags::default(),
+            };
+            tx.send(ParsedFile {
+                path,
+                state,
+                result,
+                skipped,
+                tags,
+            })
+            .unwrap();
+            WalkState::Continue
+        })
+    });
+
+    drop(tx);
+    rx.into_iter().collect()
+}
@@ -0,0 +1,120 @@
+// Minimal inotify based watcher, used by `Languages.watch` to know when to update the
+// statistics. Only the directories (and files) to be counted are watched.
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+use std::time::Duration;
+
+use ignore::WalkBuilder;
+
+const WATCH_MASK: u32 = libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MODIFY
+    | libc::IN_CLOSE_WRITE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_DELETE_SELF;
+
+// What was found reading the pending events.
+#[derive(Default)]
+pub struct Events {
+    pub changed: bool,
+    pub new_directories: bool,
+}
+
+pub struct Inotify {
+    fd: RawFd,
+}
+
+impl Inotify {
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Inotify { fd })
+    }
+
+    // Adding a path already watched is allowed, it just updates the existing watch.
+    pub fn add_watch(&self, path: &Path) -> io::Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let wd = unsafe { libc::inotify_add_watch(self.fd, path.as_ptr(), WATCH_MASK) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    // Watches every directory the walker would visit, and the paths given themselves.
+    pub fn add_walker(&self, paths: &[String], walker: WalkBuilder) -> io::Result<()> {
+        for path in paths {
+            self.add_watch(Path::new(path))?;
+        }
+        for entry in walker.build().flatten() {
+            if entry.file_type().map_or(false, |ft| ft.is_dir()) {
+                // The directory may have been removed since it was found.
+                let _ = self.add_watch(entry.path());
+            }
+        }
+        Ok(())
+    }
+
+    // Waits at most `timeout` for new events and consumes all the pending ones.
+    pub fn wait(&self, timeout: Duration) -> io::Result<Events> {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) };
+        if ready < 0 {
+            let error = io::Error::last_os_error();
+            return match error.kind() {
+                io::ErrorKind::Interrupted => Ok(Events::default()),
+                _ => Err(error),
+            };
+        }
+
+        let mut events = Events::default();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = unsafe {
+                libc::read(
+                    self.fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            };
+            if read <= 0 {
+                break;
+            }
+
+            let mut offset = 0;
+            while offset + mem::size_of::<libc::inotify_event>() <= read as usize {
+                let event: libc::inotify_event = unsafe {
+                    std::ptr::read_unaligned(buffer[offset..].as_ptr() as *const libc::inotify_event)
+                };
+                events.changed = true;
+                if event.mask & libc::IN_ISDIR != 0
+                    && event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0
+                {
+                    events.new_directories = true;
+                }
+                offset += mem::size_of::<libc::inotify_event>() + event.len as usize;
+            }
+        }
+        Ok(events)
+    }
+}
+
+impl Drop for Inotify {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
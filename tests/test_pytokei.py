@@ -4,6 +4,13 @@ files found in https://github.com/XAMPPRocky/tokei/tree/master/tests/data
 
 import os
 import pathlib
+import shutil
+import subprocess
+import sys
+import tarfile
+import threading
+import time
+import zipfile
 
 import pytest
 
@@ -20,10 +27,80 @@ class TestConfig:
 
     def test_columns(self, conf):
         assert conf.columns is None
+        conf.columns = 100
+        assert conf.columns == 100
+
+    def test_sort(self, conf):
+        assert conf.sort is None
+        conf.sort = tokei.Sort("code")
+        assert repr(conf.sort) == "Sort(Code)"
 
     def test_repr(self, conf):
         assert repr(conf) == "Config()"
 
+    def test_cache_dir(self, conf):
+        assert conf.cache_dir is None
+        conf.cache_dir = "cache"
+        assert conf.cache_dir == "cache"
+
+    def test_filters(self, conf):
+        assert conf.include == conf.exclude == []
+        assert conf.include_regex is conf.exclude_regex is None
+        conf.include = ["src/**/*.rs"]
+        conf.exclude_regex = r"_test\.py$"
+        assert conf.include == ["src/**/*.rs"]
+        assert conf.exclude_regex == r"_test\.py$"
+        conf.include = None
+        assert conf.include == []
+        with pytest.raises(ValueError, match="Invalid exclude pattern"):
+            conf.exclude = ["src/{a,b"]
+        with pytest.raises(ValueError, match="Invalid include_regex"):
+            conf.include_regex = "(unclosed"
+        assert conf.exclude == []
+
+    def test_ignore_files(self, conf, tmp_path):
+        assert conf.ignore_files == conf.custom_ignore_filenames == []
+        (tmp_path / "central.ignore").write_text("build/\n")
+        conf.ignore_files = [str(tmp_path / "central.ignore")]
+        conf.custom_ignore_filenames = [".locignore"]
+        assert conf.ignore_files == [str(tmp_path / "central.ignore")]
+        assert conf.custom_ignore_filenames == [".locignore"]
+        (tmp_path / "invalid.ignore").write_text("src/{a,b\n")
+        with pytest.raises(ValueError, match="Invalid ignore file"):
+            conf.ignore_files = [str(tmp_path / "invalid.ignore")]
+        with pytest.raises(OSError):
+            conf.ignore_files = [str(tmp_path / "missing.ignore")]
+        with pytest.raises(ValueError, match="Invalid ignore file name"):
+            conf.custom_ignore_filenames = ["src/.locignore"]
+
+    def test_walk_options(self, conf):
+        assert conf.follow_links is False
+        assert conf.max_depth is None
+        assert conf.same_file_system is False
+        conf.follow_links = True
+        conf.max_depth = 2
+        conf.same_file_system = True
+        assert conf.follow_links is True
+        assert conf.max_depth == 2
+        assert conf.same_file_system is True
+        conf.max_depth = None
+        assert conf.max_depth is None
+
+    def test_file_limits(self, conf):
+        assert conf.max_file_size is None
+        assert conf.skip_binary is conf.skip_minified is False
+        conf.max_file_size = 1024
+        conf.skip_binary = conf.skip_minified = True
+        assert conf.max_file_size == 1024
+        assert conf.skip_binary is conf.skip_minified is True
+
+    def test_test_patterns(self, conf):
+        assert conf.test_patterns == []
+        conf.test_patterns = ["e2e/", "*_check.py"]
+        assert conf.test_patterns == ["e2e/", "*_check.py"]
+        with pytest.raises(ValueError, match="Invalid test pattern"):
+            conf.test_patterns = ["e2e/{a,b"]
+
     def test_read_from_file(self, conf):
         conf = tokei.Config.from_config_files()
         assert isinstance(conf, tokei.Config)
@@ -98,6 +175,12 @@ class TestReport:
     def test_stats(self, report):
         assert isinstance(report.stats, tokei.CodeStats)
 
+    def test_tags(self, report):
+        assert report.is_generated is report.is_vendored is report.is_test is False
+
+    def test_embedded(self, report):
+        assert report.embedded() == {}
+
     def test_repr(self, report):
         # Test only that expected strings are contained
         # in the name to avoid writing the full path it shows
@@ -199,6 +282,98 @@ class TestLanguageType:
     def test_important_syntax(self, rust_lang):
         assert rust_lang.important_syntax() == ['#"', '"', "/*", "///", "//!"]
 
+    def test_classify_lines(self, rust_lang):
+        text = "// comment\n\nfn main() {}\n"
+        assert rust_lang.classify_lines(text, tokei.Config()) == [
+            ("comment", None),
+            ("blank", None),
+            ("code", None),
+        ]
+        text = "# Title\n\n```rust\n// comment\nfn main() {}\n```\n"
+        lines = tokei.LanguageType("Markdown").classify_lines(text, tokei.Config())
+        assert lines == [
+            ("comment", None),
+            ("blank", None),
+            ("comment", None),
+            ("comment", rust_lang),
+            ("code", rust_lang),
+            ("comment", None),
+        ]
+        text = "<html>\n<script>\n// comment\nlet x = 1;\n</script>\n</html>\n"
+        lines = tokei.LanguageType("HTML").classify_lines(text, tokei.Config())
+        javascript = tokei.LanguageType("JavaScript")
+        assert lines == [
+            ("code", None),
+            ("code", None),
+            ("comment", javascript),
+            ("code", javascript),
+            ("code", None),
+            ("code", None),
+        ]
+
+    @pytest.mark.parametrize(
+        "name, text",
+        [
+            (
+                "HTML",
+                "<html>\n<!-- <script>\n-->\n<script>\n/* a\n<style> */\n"
+                "let x = '</style>';\n</script>\n<style>\nbody {}\n</style>\n</html>\n",
+            ),
+            (
+                "Markdown",
+                "# Title\n\n```rust\n/// Doc with `code`.\n"
+                "fn main() {\n\n    // comment\n}\n"
+                "```\ntext\n```python\n# comment\nx = 1\n```\n```\nnot code\n```\n",
+            ),
+            (
+                "Rust",
+                "//! Crate docs.\n//!\n//! ```\n//! let x = 1;\n//! ```\n\n"
+                '/// Version.\npub const A: &str = env!("X");\n\n'
+                '/// Name.\npub const B: &str = env!("Y");\n\n'
+                '/* block\n   comment */\nfn main() {\n    let s = "/* text";\n}\n',
+            ),
+        ],
+    )
+    def test_classify_lines_counted(self, name, text):
+        # The kinds of the lines add up to the totals counted by tokei.
+        language, conf = tokei.LanguageType(name), tokei.Config()
+
+        def totals(stats, language=None, counts=None):
+            counts = {} if counts is None else counts
+            count = counts.setdefault(language, {"blank": 0, "code": 0, "comment": 0})
+            count["blank"] += stats.blanks
+            count["code"] += stats.code
+            count["comment"] += stats.comments
+            for child, blob in stats.blobs.items():
+                totals(blob, child, counts)
+            return counts
+
+        classified = {}
+        for kind, child in language.classify_lines(text, conf):
+            count = classified.setdefault(child, {"blank": 0, "code": 0, "comment": 0})
+            if kind is not None:
+                count[kind] += 1
+        assert classified == totals(language.parse_from_str(text, conf))
+
+    def test_classify_lines_uncounted(self, rust_lang):
+        # tokei reads the doc comment from the end of the line before it, so neither
+        # that line nor the blank one are counted.
+        text = '/// Version.\npub const A: &str = env!("X");\n\n'
+        text += "/// Name.\nconst B: u8 = 1;\n"
+        markdown = tokei.LanguageType("Markdown")
+        assert rust_lang.classify_lines(text, tokei.Config()) == [
+            ("comment", markdown),
+            (None, None),
+            (None, None),
+            ("comment", markdown),
+            ("code", None),
+        ]
+
+    def test_parse_from_str(self, rust_lang):
+        stats = rust_lang.parse_from_str("/// Doc.\nfn main() {}\n", tokei.Config())
+        assert (stats.code, stats.comments) == (1, 0)
+        assert stats.blobs[tokei.LanguageType("Markdown")].comments == 1
+
     @pytest.mark.skip
     def test_from_path(self):
         raise NotImplementedError
@@ -261,6 +436,945 @@ class TestLanguage:
         assert language.files() == 0
 
 
+def test_languages_update(tmp_path):
+    (tmp_path / "module.py").write_text("x = 1\n")
+    (tmp_path / "main.rs").write_text("fn main() {}\n// comment\n")
+    paths, conf = [str(tmp_path)], tokei.Config()
+    langs = tokei.Languages()
+    langs.get_statistics(paths, [], conf)
+    assert langs.update(paths, [], conf) == {"added": [], "modified": [], "removed": []}
+
+    (tmp_path / "module.py").write_text("x = 1\n\n# comment\n")
+    (tmp_path / "other.py").write_text("y = 2\n")
+    (tmp_path / "main.rs").unlink()
+    changes = langs.update(paths, [], conf)
+    assert changes == {
+        "added": [str(tmp_path / "other.py")],
+        "modified": [str(tmp_path / "module.py")],
+        "removed": [str(tmp_path / "main.rs")],
+    }
+    assert langs.language_names() == ["Python"]
+    assert langs.files() == {"Python": 2}
+    assert langs.total_plain() == {
+        "files": 2,
+        "lines": 4,
+        "code": 2,
+        "comments": 1,
+        "blanks": 1,
+    }
+
+
+def test_languages_diff(tmp_path):
+    (tmp_path / "module.py").write_text("x = 1\n")
+    (tmp_path / "main.rs").write_text("fn main() {}\n")
+    (tmp_path / "same.py").write_text("y = 2\n")
+    paths, conf = [str(tmp_path)], tokei.Config()
+    before = tokei.Languages()
+    before.get_statistics(paths, [], conf)
+
+    (tmp_path / "module.py").write_text("x = 1\n\n# comment\n")
+    (tmp_path / "other.py").write_text("z = 3\nw = 4\n")
+    (tmp_path / "main.rs").unlink()
+    after = tokei.Languages()
+    after.get_statistics(paths, [], conf)
+
+    assert before.diff(before) == {}
+    diff = before.diff(after)
+    assert sorted(lang.name() for lang in diff) == ["Python", "Rust"]
+    python = diff[tokei.LanguageType("Python")]
+    assert (python.files(), python.code, python.comments, python.blanks) == (1, 2, 1, 1)
+    assert [(f.name, f.status, f.code) for f in python.added] == [
+        (str(tmp_path / "other.py"), "added", 2)
+    ]
+    assert [(f.name, f.status, f.lines()) for f in python.changed] == [
+        (str(tmp_path / "module.py"), "changed", 2)
+    ]
+    assert python.removed == []
+    rust = diff[tokei.LanguageType("Rust")]
+    assert (rust.files(), rust.code, rust.lines()) == (-1, -1, -1)
+    assert [f.status for f in rust.removed] == ["removed"]
+
+    plain = before.diff_plain(after)
+    assert plain["Rust"] == {
+        "files": -1,
+        "lines": -1,
+        "code": -1,
+        "comments": 0,
+        "blanks": 0,
+        "added": {},
+        "removed": {
+            str(tmp_path / "main.rs"): {
+                "lines": -1,
+                "code": -1,
+                "comments": 0,
+                "blanks": 0,
+            }
+        },
+        "changed": {},
+    }
+    assert after.diff_plain(before)["Python"]["files"] == -1
+
+
+def test_languages_diff_embedded(tmp_path):
+    readme = tmp_path / "README.md"
+    readme.write_text("# Title\n\n```python\nx = 1\n```\n")
+    paths, conf = [str(tmp_path)], tokei.Config()
+    before = tokei.Languages()
+    before.get_statistics(paths, [], conf)
+
+    # Only the code block changes, the markdown counts stay the same.
+    readme.write_text("# Title\n\n```python\nx = 1\ny = 2\n```\n")
+    after = tokei.Languages()
+    after.get_statistics(paths, [], conf)
+
+    markdown = before.diff(after)[tokei.LanguageType("Markdown")]
+    assert (markdown.files(), markdown.code, markdown.lines()) == (0, 1, 1)
+    assert [(f.name, f.code) for f in markdown.changed] == [(str(readme), 1)]
+
+
+def test_languages_merge(tmp_path):
+    (tmp_path / "shard_a").mkdir()
+    (tmp_path / "shard_b").mkdir()
+    (tmp_path / "shard_a" / "module.py").write_text("x = 1\n# comment\n")
+    (tmp_path / "shard_b" / "main.rs").write_text("fn main() {}\n")
+    (tmp_path / "shard_b" / "other.py").write_text("y = 2\n\n")
+    conf = tokei.Config()
+    shard_a, shard_b = tokei.Languages(), tokei.Languages()
+    shard_a.get_statistics([str(tmp_path / "shard_a")], [], conf)
+    shard_b.get_statistics([str(tmp_path / "shard_b")], [], conf)
+    whole = tokei.Languages()
+    whole.get_statistics(
+        [str(tmp_path / "shard_a"), str(tmp_path / "shard_b")], [], conf
+    )
+
+    merged = shard_a + shard_b
+    assert merged.report_compact_plain() == whole.report_compact_plain()
+    assert shard_a.files() == {"Python": 1}
+
+    # Files found in both are only counted once.
+    merged += shard_a
+    assert merged.total_plain() == whole.total_plain()
+    shard_a.merge(merged)
+    assert shard_a.files() == {"Python": 2, "Rust": 1}
+    assert shard_a.diff(whole) == {}
+    shard_a.merge(shard_a)
+    assert shard_a.total_plain() == whole.total_plain()
+
+
+def test_languages_get_statistics_archive(tmp_path):
+    files = {
+        "project/src/module.py": "x = 1\n# comment\n\n",
+        "project/src/main.rs": "fn main() {}\n",
+        "project/script": "#!/usr/bin/env python3\nprint(1)\n",
+        "project/.hidden/module.py": "y = 2\n",
+        "project/README": "not counted\n",
+    }
+    for name, content in files.items():
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text(content)
+    with tarfile.open(tmp_path / "project.tar.gz", "w:gz") as archive:
+        archive.add(tmp_path / "project", arcname="project")
+    with zipfile.ZipFile(tmp_path / "project.zip", "w", zipfile.ZIP_DEFLATED) as zip:
+        for name in files:
+            zip.write(tmp_path / name, arcname=name)
+
+    # The hidden files and those without a known language are skipped.
+    for archive in ["project.tar.gz", "project.zip"]:
+        langs = tokei.Languages()
+        langs.get_statistics_archive(str(tmp_path / archive), tokei.Config())
+        assert langs.files() == {"Python": 2, "Rust": 1}
+        reports = langs[tokei.LanguageType("Python")].reports
+        assert sorted(r.name for r in reports) == [
+            "project/script",
+            "project/src/module.py",
+        ]
+        assert langs.total_plain() == {
+            "files": 3,
+            "lines": 6,
+            "code": 3,
+            "comments": 2,
+            "blanks": 1,
+        }
+
+    with pytest.raises(ValueError):
+        langs.get_statistics_archive(str(tmp_path / "project/script"), tokei.Config())
+    with pytest.raises(IOError):
+        langs.get_statistics_archive(str(tmp_path / "missing.zip"), tokei.Config())
+
+    # Truncated archives and files not matching their checksum are invalid.
+    content = (tmp_path / "project.tar.gz").read_bytes()
+    (tmp_path / "truncated.tar.gz").write_bytes(content[: len(content) // 2])
+    with zipfile.ZipFile(tmp_path / "stored.zip", "w", zipfile.ZIP_STORED) as zip:
+        zip.writestr("module.py", "x = 1\n")
+    content = (tmp_path / "stored.zip").read_bytes()
+    (tmp_path / "corrupt.zip").write_bytes(content.replace(b"x = 1", b"x = 2"))
+    for archive in ["truncated.tar.gz", "corrupt.zip"]:
+        with pytest.raises(ValueError):
+            langs.get_statistics_archive(str(tmp_path / archive), tokei.Config())
+
+
+def git(repo, *args):
+    config = ["-c", "user.name=pytokei", "-c", "user.email=pytokei@example.com"]
+    subprocess.run(
+        ["git", "-C", str(repo), *config, *args], check=True, capture_output=True
+    )
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_get_statistics_at_revision(tmp_path):
+    git(tmp_path, "init")
+    (tmp_path / "src").mkdir()
+    (tmp_path / "src" / "module.py").write_text("x = 1\n# comment\n")
+    (tmp_path / "script").write_text("#!/usr/bin/env python3\nprint(1)\n")
+    (tmp_path / "generated.rs").write_text("fn main() {}\n")
+    (tmp_path / ".gitignore").write_text("generated.rs\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "add", "-f", "generated.rs")
+    git(tmp_path, "commit", "-m", "first")
+    git(tmp_path, "tag", "v1")
+    (tmp_path / "src" / "module.py").write_text("x = 1\n")
+    (tmp_path / "src" / "other.py").write_text("y = 2\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "second")
+    # Changes not committed are not counted.
+    (tmp_path / "src" / "new.py").write_text("z = 3\n")
+
+    conf = tokei.Config()
+    langs = tokei.Languages()
+    langs.get_statistics_at_revision(str(tmp_path), "v1", conf)
+    assert langs.language_names() == ["Python"]
+    assert sorted(r.name for r in langs[tokei.LanguageType("Python")].reports) == [
+        str(tmp_path / "script"),
+        str(tmp_path / "src" / "module.py"),
+    ]
+    assert langs.total_plain() == {
+        "files": 2,
+        "lines": 4,
+        "code": 2,
+        "comments": 2,
+        "blanks": 0,
+    }
+
+    langs = tokei.Languages()
+    langs.get_statistics_at_revision(str(tmp_path), "HEAD", conf)
+    assert langs.files() == {"Python": 3}
+    assert langs.total_plain()["code"] == 3
+
+    with pytest.raises(ValueError):
+        langs.get_statistics_at_revision(str(tmp_path), "undefined", conf)
+    with pytest.raises(ValueError):
+        langs.get_statistics_at_revision(str(tmp_path / "src"), "HEAD~5", conf)
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_git_subdirectory(tmp_path):
+    git(tmp_path, "init")
+    (tmp_path / "sub" / "inner").mkdir(parents=True)
+    (tmp_path / "top.py").write_text("x = 1\n")
+    (tmp_path / ".gitignore").write_text("ignored.rs\n")
+    (tmp_path / "sub" / "main.rs").write_text("fn main() {}\n")
+    (tmp_path / "sub" / "ignored.rs").write_text("fn f() {}\n")
+    (tmp_path / "sub" / "inner" / "module.py").write_text("y = 2\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "first")
+    (tmp_path / "top.py").write_text("x = 1\nx = 2\n")
+    (tmp_path / "sub" / "main.rs").write_text("fn main() {}\nfn g() {}\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "second")
+
+    # Only the files below the path are counted, with the names found walking it.
+    sub, conf = tmp_path / "sub", tokei.Config()
+    langs = tokei.Languages()
+    langs.get_statistics_at_revision(str(sub), "HEAD", conf)
+    walked = tokei.Languages()
+    walked.get_statistics([str(sub)], [], conf)
+    assert langs.report_compact_plain() == walked.report_compact_plain()
+    reports = langs.get_languages().values()
+    assert sorted(r.name for lang in reports for r in lang.reports) == [
+        str(sub / "inner" / "module.py"),
+        str(sub / "main.rs"),
+    ]
+
+    diff = tokei.Languages().get_statistics_for_diff(str(sub), "HEAD~1", "HEAD", conf)
+    assert list(diff) == [tokei.LanguageType("Rust")]
+    assert diff[tokei.LanguageType("Rust")]["added"].code == 1
+
+    authors = tokei.Languages().get_statistics_by_author(str(sub), "HEAD", conf)
+    assert sorted(authors["pytokei"]) == sorted(
+        [tokei.LanguageType("Python"), tokei.LanguageType("Rust")]
+    )
+    assert sum(stats.code for stats in authors["pytokei"].values()) == 3
+
+    commits = list(tokei.history(str(sub)))
+    assert [langs.files() for _, _, langs in commits] == [{"Python": 1, "Rust": 1}] * 2
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_get_statistics_for_diff(tmp_path):
+    git(tmp_path, "init")
+    (tmp_path / "module.py").write_text("x = 1\n# comment\n\ny = 2\n")
+    (tmp_path / "removed.py").write_text("z = 3\n")
+    (tmp_path / "unchanged.rs").write_text("fn main() {}\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "base")
+    (tmp_path / "module.py").write_text('x = 1\n"""doc"""\n\ny = 4\nw = 5\n')
+    (tmp_path / "removed.py").unlink()
+    (tmp_path / "lib.rs").write_text("// lib\npub fn f() {}\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "head")
+
+    langs = tokei.Languages()
+    diff = langs.get_statistics_for_diff(
+        str(tmp_path), "HEAD~1", "HEAD", tokei.Config()
+    )
+    python = diff[tokei.LanguageType("Python")]
+    # Docstrings are code unless `treat_doc_strings_as_comments` is set.
+    assert python["added"].plain() == {
+        "blanks": 0,
+        "code": 3,
+        "comments": 0,
+        "lines": 3,
+    }
+    assert python["removed"].plain() == {
+        "blanks": 0,
+        "code": 2,
+        "comments": 1,
+        "lines": 3,
+    }
+    rust = diff[tokei.LanguageType("Rust")]
+    assert (rust["added"].code, rust["added"].comments) == (1, 1)
+    assert rust["removed"].lines() == 0
+    # Only the files changed are counted, as they are in head.
+    assert langs.files() == {"Python": 1, "Rust": 1}
+
+    with pytest.raises(ValueError):
+        langs.get_statistics_for_diff(
+            str(tmp_path), "HEAD~1", "undefined", tokei.Config()
+        )
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_get_statistics_for_diff_state(tmp_path):
+    # The changed lines are classified with the comments and blocks opened before them,
+    # the ones tokei doesn't count aren't added.
+    git(tmp_path, "init")
+    page = "<html>\n<script>\n/* a\n{}\nlet x = {};\n</script>\n</html>\n"
+    source = '/// Version.\npub const A: &str = env!("{}");\n\n'
+    source += "/// Name.\nconst B: u8 = {};\n"
+    (tmp_path / "index.html").write_text(page.format("b */", 1))
+    (tmp_path / "lib.rs").write_text(source.format("X", 1))
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "base")
+    (tmp_path / "index.html").write_text(page.format("c */", 2))
+    (tmp_path / "lib.rs").write_text(source.format("Y", 2))
+    git(tmp_path, "commit", "-am", "head")
+
+    diff = tokei.Languages().get_statistics_for_diff(
+        str(tmp_path), "HEAD~1", "HEAD", tokei.Config()
+    )
+    html = diff[tokei.LanguageType("HTML")]
+    rust = diff[tokei.LanguageType("Rust")]
+    for change in ("added", "removed"):
+        assert html[change].plain() == {
+            "blanks": 0,
+            "code": 1,
+            "comments": 1,
+            "lines": 2,
+        }
+        assert rust[change].plain() == {
+            "blanks": 0,
+            "code": 1,
+            "comments": 0,
+            "lines": 1,
+        }
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_get_statistics_by_author(tmp_path):
+    git(tmp_path, "init")
+    (tmp_path / "module.py").write_text("x = 1\n# comment\n")
+    (tmp_path / "main.rs").write_text("fn main() {}\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "first")
+    (tmp_path / "module.py").write_text("x = 1\n# comment\n\ny = 2\n")
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "second", "--author", "Other <other@example.com>")
+
+    langs = tokei.Languages()
+    authors = langs.get_statistics_by_author(str(tmp_path), "HEAD", tokei.Config())
+    python, rust = tokei.LanguageType("Python"), tokei.LanguageType("Rust")
+    assert sorted(authors) == ["Other", "pytokei"]
+    assert authors["pytokei"][python].plain() == {
+        "blanks": 0,
+        "code": 1,
+        "comments": 1,
+        "lines": 2,
+    }
+    assert authors["pytokei"][rust].code == 1
+    assert list(authors["Other"]) == [python]
+    assert authors["Other"][python].plain() == {
+        "blanks": 1,
+        "code": 1,
+        "comments": 0,
+        "lines": 2,
+    }
+    # The struct is populated with the statistics of the revision.
+    assert langs.files() == {"Python": 1, "Rust": 1}
+
+    authors = tokei.Languages().get_statistics_by_author(
+        str(tmp_path), "HEAD~1", tokei.Config()
+    )
+    assert list(authors) == ["pytokei"]
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_get_statistics_by_author_embedded(tmp_path):
+    # The lines are attributed with the kinds tokei counts, in embedded blocks too, and
+    # the lines it doesn't count aren't attributed.
+    git(tmp_path, "init")
+    page = "<html>\n<script>\n/* a\n{}\nlet x = 1;\n</script>\n\n</html>\n"
+    source = '/// Version.\npub const A: &str = env!("{}");\n\n'
+    source += "/// Name.\nconst B: u8 = 1;\n"
+    (tmp_path / "index.html").write_text(page.format("b */"))
+    (tmp_path / "lib.rs").write_text(source.format("X"))
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "first")
+    (tmp_path / "index.html").write_text(page.format("c */"))
+    (tmp_path / "lib.rs").write_text(source.format("Y"))
+    git(tmp_path, "commit", "-am", "second", "--author", "Other <other@example.com>")
+
+    langs = tokei.Languages()
+    authors = langs.get_statistics_by_author(str(tmp_path), "HEAD", tokei.Config())
+    html, rust = tokei.LanguageType("HTML"), tokei.LanguageType("Rust")
+    assert list(authors["Other"]) == [html]
+    assert authors["Other"][html].plain() == {
+        "blanks": 0,
+        "code": 0,
+        "comments": 1,
+        "lines": 1,
+    }
+    for language in (html, rust):
+        totals = langs[language].summarise()
+        attributed = [by[language] for by in authors.values() if language in by]
+        for kind in ("blanks", "code", "comments"):
+            total = sum(getattr(stats, kind) for stats in attributed)
+            assert total == getattr(totals, kind)
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_history(tmp_path):
+    git(tmp_path, "init")
+    for i in range(5):
+        (tmp_path / f"module_{i}.py").write_text("x = 1\n" * (i + 1))
+        git(tmp_path, "add", "-A")
+        git(tmp_path, "commit", "-m", f"commit {i}")
+
+    commits = list(tokei.history(str(tmp_path)))
+    assert len(commits) == 5
+    assert [langs.total_plain()["code"] for _, _, langs in commits] == [1, 3, 6, 10, 15]
+    commit, timestamp, langs = commits[-1]
+    assert isinstance(timestamp, int)
+    at_revision = tokei.Languages()
+    at_revision.get_statistics_at_revision(str(tmp_path), commit, tokei.Config())
+    assert langs.report_compact_plain() == at_revision.report_compact_plain()
+
+    history = tokei.history(str(tmp_path), every_n=2)
+    assert len(history) == 3
+    assert [langs.files()["Python"] for _, _, langs in history] == [1, 3, 5]
+    history = tokei.history(str(tmp_path), revs=["HEAD~2..HEAD~1"])
+    assert [langs.files()["Python"] for _, _, langs in history] == [4]
+
+    with pytest.raises(ValueError):
+        tokei.history(str(tmp_path), every_n=0)
+    with pytest.raises(ValueError):
+        tokei.history(str(tmp_path), revs=["undefined"])
+
+
+@pytest.mark.skipif(sys.platform != "linux", reason="watch is only available on linux")
+def test_languages_watch(tmp_path):
+    (tmp_path / "module.py").write_text("x = 1\n")
+    paths, conf = [str(tmp_path)], tokei.Config()
+    langs = tokei.Languages()
+    langs.get_statistics(paths, [], conf)
+
+    def modify():
+        time.sleep(0.3)
+        (tmp_path / "package").mkdir()
+        time.sleep(0.3)
+        (tmp_path / "package" / "other.py").write_text("y = 2\nz = 3\n")
+
+    calls = []
+
+    def callback(changes, totals):
+        calls.append((changes, totals))
+        return False
+
+    threading.Thread(target=modify).start()
+    langs.watch(paths, conf, callback, timeout=10)
+    assert len(calls) == 1
+    changes, totals = calls[0]
+    assert [r.name for r in changes["added"]] == [
+        str(tmp_path / "package" / "other.py")
+    ]
+    assert changes["modified"] == changes["removed"] == []
+    assert totals["code"] == 3
+    assert langs.files() == {"Python": 2}
+
+
+def test_languages_filters(tmp_path):
+    files = [
+        "src/main.rs",
+        "src/generated/bindings.rs",
+        "src/module_test.py",
+        "tests/test.rs",
+        "setup.py",
+    ]
+    for name in files:
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text("x\n")
+
+    def counted(**filters):
+        conf = tokei.Config()
+        for name, value in filters.items():
+            setattr(conf, name, value)
+        langs = tokei.Languages()
+        langs.get_statistics([str(tmp_path)], [], conf)
+        return sorted(
+            str(pathlib.Path(report.name).relative_to(tmp_path))
+            for language in langs.get_languages().values()
+            for report in language.reports
+        )
+
+    assert counted() == sorted(files)
+    assert counted(include=["src/**/*.rs"]) == [
+        "src/generated/bindings.rs",
+        "src/main.rs",
+    ]
+    assert counted(include=["*.py"]) == ["setup.py", "src/module_test.py"]
+    assert counted(include=["/src", "!*.py"], exclude=["**/generated/**"]) == [
+        "src/main.rs"
+    ]
+    assert counted(exclude=["generated/", "tests"]) == [
+        "setup.py",
+        "src/main.rs",
+        "src/module_test.py",
+    ]
+    assert counted(include_regex=r"^src/", exclude_regex=r"_test\.py$") == [
+        "src/generated/bindings.rs",
+        "src/main.rs",
+    ]
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_filters_revision_and_archive(tmp_path):
+    files = ["src/main.rs", "src/generated/bindings.rs", "setup.py"]
+    for name in files:
+        (tmp_path / "project" / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / "project" / name).write_text("x\n")
+    git(tmp_path / "project", "init")
+    git(tmp_path / "project", "add", "-A")
+    git(tmp_path / "project", "commit", "-m", "first")
+    with zipfile.ZipFile(tmp_path / "project.zip", "w") as zip:
+        for name in files:
+            zip.write(tmp_path / "project" / name, arcname=name)
+
+    # The paths are relative to the repository path and to the root of the archive.
+    conf = tokei.Config()
+    conf.include = ["src/**/*.rs"]
+    conf.exclude_regex = "generated"
+    at_revision, archive = tokei.Languages(), tokei.Languages()
+    at_revision.get_statistics_at_revision(str(tmp_path / "project"), "HEAD", conf)
+    archive.get_statistics_archive(str(tmp_path / "project.zip"), conf)
+    assert [r.name for r in at_revision[tokei.LanguageType("Rust")].reports] == [
+        str(tmp_path / "project" / "src" / "main.rs")
+    ]
+    assert [r.name for r in archive[tokei.LanguageType("Rust")].reports] == [
+        "src/main.rs"
+    ]
+    assert at_revision.files() == archive.files() == {"Rust": 1}
+
+
+def test_languages_ignore_files(tmp_path):
+    for name in ["project/main.py", "project/gen/code.py", "project/build/out.py"]:
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text("x = 1\n")
+    (tmp_path / "project" / ".locignore").write_text("gen/\n")
+    (tmp_path / "central.ignore").write_text("/build\n")
+
+    conf = tokei.Config()
+    conf.custom_ignore_filenames = [".locignore"]
+    conf.ignore_files = [str(tmp_path / "central.ignore")]
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path / "project")], [], conf)
+    reports = langs[tokei.LanguageType("Python")].reports
+    assert [r.name for r in reports] == [str(tmp_path / "project" / "main.py")]
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="symlinks need privileges")
+def test_languages_walk_options(tmp_path):
+    for name in ["project/main.py", "project/a/b/deep.py", "other/linked.py"]:
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text("x = 1\n")
+    (tmp_path / "project" / "link").symlink_to(tmp_path / "other")
+    (tmp_path / "project" / "a" / "loop").symlink_to(tmp_path / "project")
+
+    def counted(**options):
+        conf = tokei.Config()
+        for name, value in options.items():
+            setattr(conf, name, value)
+        langs = tokei.Languages()
+        langs.get_statistics([str(tmp_path / "project")], [], conf)
+        reports = langs[tokei.LanguageType("Python")].reports
+        return sorted(os.path.relpath(r.name, tmp_path / "project") for r in reports)
+
+    assert counted() == [os.path.join("a", "b", "deep.py"), "main.py"]
+    assert counted(follow_links=True) == [
+        os.path.join("a", "b", "deep.py"),
+        os.path.join("link", "linked.py"),
+        "main.py",
+    ]
+    assert counted(max_depth=1) == ["main.py"]
+    assert counted(same_file_system=True) == counted()
+
+
+def test_languages_skipped(tmp_path):
+    (tmp_path / "main.py").write_text("x = 1\n")
+    (tmp_path / "big.py").write_text("x = 1\n" * 1000)
+    (tmp_path / "app.min.js").write_text("var a = 1;" * 50 + "\n")
+    (tmp_path / "data.py").write_bytes(b"x = 1\n\x00\x01\n")
+
+    conf = tokei.Config()
+    conf.max_file_size = 1000
+    conf.skip_binary = conf.skip_minified = True
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], conf)
+    assert langs.files() == {"Python": 1}
+    assert langs.skipped() == [
+        (str(tmp_path / "app.min.js"), "minified"),
+        (str(tmp_path / "big.py"), "max_file_size"),
+        (str(tmp_path / "data.py"), "binary"),
+    ]
+    assert tokei.Languages().skipped() == []
+
+    # The files are checked again on every update.
+    (tmp_path / "big.py").write_text("x = 1\n")
+    changes = langs.update([str(tmp_path)], [], conf)
+    assert changes["added"] == [str(tmp_path / "big.py")]
+    assert [path for path, _ in langs.skipped()] == [
+        str(tmp_path / "app.min.js"),
+        str(tmp_path / "data.py"),
+    ]
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_skipped_revision_and_archive(tmp_path):
+    files = {
+        "main.py": b"x = 1\n",
+        "big.py": b"x = 1\n" * 1000,
+        "app.min.js": b"var a = 1;" * 50 + b"\n",
+        "data.py": b"x = 1\n\x00\x01\n",
+    }
+    git(tmp_path, "init")
+    for name, content in files.items():
+        (tmp_path / name).write_bytes(content)
+    git(tmp_path, "add", "-A")
+    git(tmp_path, "commit", "-m", "first")
+    with zipfile.ZipFile(tmp_path / "project.zip", "w") as zip:
+        for name in files:
+            zip.write(tmp_path / name, arcname=name)
+
+    conf = tokei.Config()
+    conf.max_file_size = 1000
+    conf.skip_binary = conf.skip_minified = True
+    at_revision, archive = tokei.Languages(), tokei.Languages()
+    at_revision.get_statistics_at_revision(str(tmp_path), "HEAD", conf)
+    archive.get_statistics_archive(str(tmp_path / "project.zip"), conf)
+    skipped = [("app.min.js", "minified"), ("big.py", "max_file_size")]
+    skipped.append(("data.py", "binary"))
+    assert at_revision.skipped() == [(str(tmp_path / n), r) for n, r in skipped]
+    assert archive.skipped() == skipped
+    assert at_revision.files() == archive.files() == {"Python": 1}
+
+    authors = tokei.Languages().get_statistics_by_author(str(tmp_path), "HEAD", conf)
+    assert authors["pytokei"][tokei.LanguageType("Python")].code == 1
+
+    # The files skipped count as removed, those of head are reported.
+    (tmp_path / "main.py").write_text("x = 1\n" * 500)
+    git(tmp_path, "commit", "-am", "second")
+    langs = tokei.Languages()
+    diff = langs.get_statistics_for_diff(str(tmp_path), "HEAD~1", "HEAD", conf)
+    python = diff[tokei.LanguageType("Python")]
+    assert (python["added"].code, python["removed"].code) == (0, 1)
+    assert langs.skipped() == [(str(tmp_path / "main.py"), "max_file_size")]
+
+
+def test_languages_generated_vendored(tmp_path):
+    files = {
+        "src/main.go": "package main\n",
+        "src/api.pb.go": "// Code generated by protoc. DO NOT EDIT.\npackage main\n",
+        "src/schema.py": "# @generated\nx = 1\n",
+        "src/bindings.py": "x = 1\n",
+        "vendor/lib/lib.go": "package lib\n",
+        "third_party/own/own.go": "package own\n",
+        ".gitattributes": "src/bindings.py linguist-generated\n"
+        "third_party/own/** -linguist-vendored\n",
+    }
+    for name, content in files.items():
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text(content)
+
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], tokei.Config())
+    tags = {
+        os.path.relpath(r.name, tmp_path): (r.is_generated, r.is_vendored)
+        for lang in langs.get_languages().values()
+        for r in lang.reports
+    }
+    assert tags == {
+        os.path.join("src", "main.go"): (False, False),
+        os.path.join("src", "api.pb.go"): (True, False),
+        os.path.join("src", "schema.py"): (True, False),
+        os.path.join("src", "bindings.py"): (True, False),
+        os.path.join("vendor", "lib", "lib.go"): (False, True),
+        os.path.join("third_party", "own", "own.go"): (False, False),
+    }
+    assert langs.filter(generated=False, vendored=False).files() == {"Go": 2}
+    assert langs.filter(generated=True).files() == {"Go": 1, "Python": 2}
+    assert langs.filter(vendored=True).total_plain()["code"] == 1
+    assert langs.filter().total_plain() == langs.total_plain()
+    reports = tokei.iter_reports([str(tmp_path / "src")], [], tokei.Config())
+    assert sum(r.is_generated for _, r in reports) == 3
+
+    # Only the directories below the path walked are used, for relative paths too.
+    cwd = os.getcwd()
+    os.chdir(tmp_path)
+    try:
+        for path in [str(tmp_path / "vendor" / "lib"), os.path.join("vendor", "lib")]:
+            langs = tokei.Languages()
+            langs.get_statistics([path], [], tokei.Config())
+            assert langs.split_by("vendored")["vendored"].reports == []
+            assert langs.files() == {"Go": 1}
+    finally:
+        os.chdir(cwd)
+
+
+@pytest.mark.skipif(shutil.which("git") is None, reason="git is not installed")
+def test_languages_tags_revision_and_archive(tmp_path):
+    files = {
+        "src/main.go": "package main\n",
+        "src/api.pb.go": "// Code generated by protoc. DO NOT EDIT.\npackage main\n",
+        "vendor/lib/lib.go": "package lib\n",
+        "src/main_test.go": "package main\n",
+    }
+    project = tmp_path / "project"
+    for name, content in files.items():
+        (project / name).parent.mkdir(parents=True, exist_ok=True)
+        (project / name).write_text(content)
+    git(project, "init")
+    git(project, "add", "-A")
+    git(project, "commit", "-m", "first")
+    git(project, "commit", "--allow-empty", "-m", "second")
+    with zipfile.ZipFile(tmp_path / "project.zip", "w") as zip:
+        for name in files:
+            zip.write(project / name, arcname=name)
+
+    def tags(langs, root):
+        return {
+            os.path.relpath(r.name, root): (r.is_generated, r.is_vendored, r.is_test)
+            for r in langs[tokei.LanguageType("Go")].reports
+        }
+
+    expected = {
+        os.path.join("src", "main.go"): (False, False, False),
+        os.path.join("src", "api.pb.go"): (True, False, False),
+        os.path.join("vendor", "lib", "lib.go"): (False, True, False),
+        os.path.join("src", "main_test.go"): (False, False, True),
+    }
+    at_revision, archive = tokei.Languages(), tokei.Languages()
+    at_revision.get_statistics_at_revision(str(project), "HEAD", tokei.Config())
+    archive.get_statistics_archive(str(tmp_path / "project.zip"), tokei.Config())
+    assert tags(at_revision, project) == tags(archive, ".") == expected
+    assert archive.filter(generated=False, vendored=False).files() == {"Go": 2}
+    # The stats of the second commit are reused from the first one.
+    assert [tags(langs, project) for _, _, langs in tokei.history(str(project))] == [
+        expected,
+        expected,
+    ]
+
+
+def test_languages_split_by_test(tmp_path):
+    for name in [
+        "src/app.py",
+        "src/test_app.py",
+        "src/server.go",
+        "src/server_test.go",
+        "tests/helpers.rs",
+        "e2e/flow.js",
+    ]:
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text("x = 1\ny = 2\n")
+
+    conf = tokei.Config()
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], conf)
+    split = langs.split_by("test")
+    assert split.keys() == {"test", "production"}
+    assert (split["test"].code, split["production"].code) == (6, 6)
+    assert langs.filter(test=True).files() == {"Go": 1, "Python": 1, "Rust": 1}
+
+    conf.test_patterns = ["e2e/"]
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], conf)
+    tests = {
+        os.path.relpath(r.name, tmp_path)
+        for lang in langs.filter(test=True).get_languages().values()
+        for r in lang.reports
+    }
+    assert os.path.join("e2e", "flow.js") in tests
+    assert langs.split_by("vendored").keys() == {"vendored", "first_party"}
+    with pytest.raises(ValueError, match="Invalid category"):
+        langs.split_by("docs")
+
+    # The files of archives are split in the same way.
+    with tarfile.open(tmp_path / "project.tar", "w") as archive:
+        for name in ["src", "tests", "e2e"]:
+            archive.add(tmp_path / name, arcname=name)
+    archive = tokei.Languages()
+    archive.get_statistics_archive(str(tmp_path / "project.tar"), conf)
+    split = archive.split_by("test")
+    assert (split["test"].code, split["production"].code) == (8, 4)
+
+
+def test_languages_tree(tmp_path):
+    files = {
+        "setup.py": "x = 1\n",
+        "pkg_a/core/main.py": "x = 1\ny = 2\n",
+        "pkg_a/lib.rs": "fn main() {}\n",
+        "pkg_b/util.py": "x = 1\n\n",
+    }
+    for name, content in files.items():
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text(content)
+
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], tokei.Config())
+    python, rust = tokei.LanguageType("Python"), tokei.LanguageType("Rust")
+
+    tree = langs.tree()
+    assert tree.keys() == {".", "pkg_a", "pkg_b"}
+    assert tree["."][python].code == 4
+    assert {ltype: stats.code for ltype, stats in tree["pkg_a"].items()} == {
+        python: 2,
+        rust: 1,
+    }
+    assert tree["pkg_b"][python].plain() == {
+        "blanks": 1,
+        "code": 1,
+        "comments": 0,
+        "lines": 2,
+    }
+    assert langs.tree(depth=2).keys() == {".", "pkg_a", "pkg_a/core", "pkg_b"}
+    assert langs.tree(depth=0).keys() == {"."}
+    assert langs.tree(root=str(tmp_path / "pkg_a")).keys() == {".", "core"}
+    assert tokei.Languages().tree() == {}
+
+
+def test_languages_group_by_codeowners(tmp_path):
+    for name in ["build.py", "src/main.rs", "src/api/handler.rs", "web/app.js"]:
+        (tmp_path / name).parent.mkdir(parents=True, exist_ok=True)
+        (tmp_path / name).write_text("x = 1\n")
+    (tmp_path / ".github").mkdir()
+    (tmp_path / ".github" / "CODEOWNERS").write_text(
+        "# Comment\n"
+        "src/ @org/core\n"
+        "/src/api/ @org/api @alice\n"
+        "[Frontend] @org/frontend\n"
+        "web/\n"
+    )
+
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], tokei.Config())
+    owners = langs.group_by_codeowners(str(tmp_path / ".github" / "CODEOWNERS"))
+    rust, python = tokei.LanguageType("Rust"), tokei.LanguageType("Python")
+    assert {owner: list(stats) for owner, stats in owners.items()} == {
+        "@org/core": [rust],
+        "@org/api": [rust],
+        "@alice": [rust],
+        "@org/frontend": [tokei.LanguageType("JavaScript")],
+        "unowned": [python],
+    }
+    assert owners["@org/core"][rust].code == owners["@org/api"][rust].code == 1
+
+    (tmp_path / "CODEOWNERS").write_text("src/{a @org/core\n")
+    with pytest.raises(ValueError, match="Invalid CODEOWNERS file"):
+        langs.group_by_codeowners(str(tmp_path / "CODEOWNERS"))
+    with pytest.raises(OSError):
+        langs.group_by_codeowners(str(tmp_path / "missing"))
+
+
+def test_languages_embedded_totals(tmp_path):
+    (tmp_path / "README.md").write_text(
+        "# Title\n\n```python\nx = 1\n```\n\n```python\n# comment\ny = 2\n```\n"
+    )
+    (tmp_path / "docs.md").write_text("Text.\n\n```bash\necho 1\n```\n")
+    (tmp_path / "main.py").write_text("x = 1\n")
+
+    langs = tokei.Languages()
+    langs.get_statistics([str(tmp_path)], [], tokei.Config())
+    markdown = tokei.LanguageType("Markdown")
+    python, bash = tokei.LanguageType("Python"), tokei.LanguageType("BASH")
+    reports = {pathlib.Path(r.name).name: r for r in langs[markdown].reports}
+    readme = reports["README.md"]
+    assert list(readme.embedded()) == [python]
+    assert readme.embedded()[python].code == 2
+    assert readme.embedded()[python].comments == 1
+
+    totals = langs.embedded_totals()
+    assert list(totals) == [markdown]
+    assert totals[markdown][python].code == 2
+    assert totals[markdown][bash].code == 1
+
+
+def test_languages_cache_dir(tmp_path):
+    conf = tokei.Config()
+    conf.cache_dir = str(tmp_path / "cache")
+    for _ in range(2):
+        langs = tokei.Languages()
+        langs.get_statistics([str(SAMPLE_FILES_PATH)], ["ignored"], conf)
+        assert langs.total_plain()["code"] == 66
+        assert langs[tokei.LanguageType("Rust")].children_plain()["Markdown"]
+    entries = [p for p in (tmp_path / "cache").rglob("*") if p.is_file()]
+    assert len(entries) == 5
+
+
+def test_iter_reports():
+    reports = tokei.iter_reports([str(SAMPLE_FILES_PATH)], ["ignored"], tokei.Config())
+    assert isinstance(reports, tokei.ReportsIterator)
+    found = list(reports)
+    assert len(found) == 5
+    assert all(isinstance(lang_type, tokei.LanguageType) for lang_type, _ in found)
+    assert all(isinstance(report, tokei.Report) for _, report in found)
+    assert sum(report.stats.code for _, report in found) == 66
+    assert reports.inaccurate is False
+    with pytest.raises(StopIteration):
+        next(reports)
+    with pytest.raises(ValueError):
+        tokei.iter_reports([], [], tokei.Config())
+
+
+@pytest.mark.skipif(
+    not hasattr(os, "geteuid") or os.geteuid() == 0,
+    reason="the files can't be made unreadable",
+)
+def test_iter_reports_inaccurate(tmp_path):
+    (tmp_path / "module.py").write_text("x = 1\n")
+    (tmp_path / "secret.py").write_text("y = 2\n")
+    (tmp_path / "secret.py").chmod(0)
+    reports = tokei.iter_reports([str(tmp_path)], [], tokei.Config())
+    assert [report.name for _, report in reports] == [str(tmp_path / "module.py")]
+    assert reports.inaccurate is True
+
+
 # TODO: Needs checks for possible errors.
 
 # These should be considered integration tests, the code
@@ -376,3 +1490,138 @@ class TestPytokei:
         assert all(
             [report["Dockerfile"][k] == v for k, v in zip(stats, values_dockerfile)]
         )
+
+    def test_languages_to_markdown(self, languages):
+        markdown = languages.to_markdown()
+        lines = markdown.splitlines()
+        assert lines[0] == "| Language | Files | Lines | Code | Comments | Blanks |"
+        assert "| Dockerfile | 1 | 16 | 7 | 3 | 6 |" in lines
+        assert lines[-1].startswith("| **Total** | **5** |")
+        assert len(lines) == 7
+        assert len(languages.to_markdown(totals=False).splitlines()) == 6
+        with_files = languages.to_markdown(files=True).splitlines()
+        assert len(with_files) == 12
+        assert any(
+            line.startswith("| `") and "python1.py" in line for line in with_files
+        )
+        by_code = languages.to_markdown(sort=tokei.Sort("code")).splitlines()
+        assert [line.split()[1] for line in by_code[2:6]] == [
+            "Rust",
+            "Python",
+            "Dockerfile",
+            "TOML",
+        ]
+
+    def test_languages_to_html(self, languages):
+        html = languages.to_html(files=True)
+        assert html.startswith("<table>")
+        assert html.count('<tr class="language">') == 4
+        assert html.count('<tr class="file">') == 5
+        assert "<tfoot>" in html
+        assert "<tfoot>" not in languages.to_html(totals=False)
+
+    def test_languages_format_table(self, languages):
+        table = languages.format_table()
+        lines = table.splitlines()
+        assert lines[0] == "=" * 79
+        assert lines[1].split() == [
+            "Language",
+            "Files",
+            "Lines",
+            "Code",
+            "Comments",
+            "Blanks",
+        ]
+        assert " |- Markdown" in table
+        assert lines[-2].split() == ["Total", "5", "97", "66", "13", "18"]
+        assert all(len(line) <= 79 for line in lines)
+        assert " |- Markdown" not in languages.format_table(compact=True)
+        wide = languages.format_table(columns=100, files=True)
+        assert wide.splitlines()[0] == "=" * 100
+        assert "python1.py" in wide
+
+    def test_languages_format_table_config(self):
+        conf = tokei.Config()
+        conf.columns = 90
+        conf.sort = tokei.Sort("lines")
+        langs = tokei.Languages()
+        langs.get_statistics([str(SAMPLE_FILES_PATH)], ["ignored"], conf)
+        lines = langs.format_table().splitlines()
+        assert lines[0] == "=" * 90
+        # Languages with children are printed at the end
+        assert [line.split()[0] for line in lines[3:6]] == [
+            "Python",
+            "Dockerfile",
+            "TOML",
+        ]
+
+    def test_languages_badge(self, languages):
+        badge = languages.badge()
+        assert badge.startswith("<svg")
+        assert 'aria-label="code: 69"' in badge
+        assert "linearGradient" in badge
+        badge = languages.badge("lines", tokei.LanguageType("Rust"), "flat-square")
+        assert 'aria-label="Rust lines: 47"' in badge
+        assert "linearGradient" not in badge
+        assert 'aria-label="files: 5"' in languages.badge(metric="files")
+        with pytest.raises(ValueError):
+            languages.badge("undefined")
+        with pytest.raises(ValueError):
+            languages.badge(style="undefined")
+        with pytest.raises(ValueError):
+            languages.badge(language=tokei.LanguageType("Java"))
+
+    @pytest.mark.parametrize(
+        "code, value", [(1_049, "1.0k"), (999_949, "999.9k"), (999_950, "1.0M")]
+    )
+    def test_languages_badge_rounding(self, tmp_path, code, value):
+        (tmp_path / "main.py").write_text("x\n" * code)
+        langs = tokei.Languages()
+        langs.get_statistics([str(tmp_path)], [], tokei.Config())
+        assert f'aria-label="code: {value}"' in langs.badge()
+
+    def test_code_stats_operators(self, languages):
+        stats = languages[tokei.LanguageType("Rust")].reports[0].stats
+        empty = tokei.CodeStats()
+        assert stats == stats
+        assert stats != empty
+        assert stats + empty == stats
+        assert (stats + stats).code == 2 * stats.code
+        assert (stats + stats) - stats == stats
+        assert sum([stats, stats, stats]) == stats + stats + stats
+        total = tokei.CodeStats()
+        total += stats
+        assert total == stats
+        with pytest.raises(ValueError):
+            empty - stats
+        with pytest.raises(TypeError):
+            stats < stats
+
+    def test_report_operators(self, languages):
+        report = languages[tokei.LanguageType("Rust")].reports[0]
+        assert report == report
+        assert (report + report.stats).stats == report.stats + report.stats
+        assert (report - report.stats).stats == tokei.CodeStats()
+        assert report - report.stats != report
+        with pytest.raises(ValueError):
+            tokei.Report("empty") - report.stats
+
+    def test_language_operators(self, languages):
+        python = languages[tokei.LanguageType("Python")]
+        rust = languages[tokei.LanguageType("Rust")]
+        assert python == python
+        assert python != rust
+        assert sum(python.reports) == python
+        combined = python + rust
+        assert combined.code == python.code + rust.code
+        assert combined.files() == python.files() + rust.files()
+        assert combined - rust == python
+        assert sum([python, rust]) == combined
+        language = tokei.Language()
+        for report in python.reports:
+            language += report
+        language += rust
+        assert language == combined
+        assert set(language.children) == {tokei.LanguageType("Markdown")}
+        with pytest.raises(ValueError):
+            python - rust